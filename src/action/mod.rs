@@ -1,6 +1,8 @@
 use crate::kbd::action::*;
-use crate::kbd::Kbd;
 use crate::kbd::ActionReceiver;
+use crate::kbd::Kbd;
+use crate::model::file::attr::Attribute;
+use crate::model::file::LinkKind;
 use crate::model::result::Res;
 use crate::model::state::list::{FileSortBy, MarkerTrait, SelectorTrait};
 use crate::model::state::workspace::Workspace;
@@ -11,14 +13,22 @@ fn ok<T>(_: T) -> Res<()> {
     Ok(())
 }
 
-pub async fn init_action(ac: ActionReceiver, mut ws: Workspace, sender: UIEventSender, kbd: Arc<Kbd>) {
+pub async fn init_action(
+    ac: ActionReceiver,
+    mut ws: Workspace,
+    sender: UIEventSender,
+    kbd: Arc<Kbd>,
+) {
     tokio::spawn(async move {
-        while let Ok(s) = ac.0.recv() {
+        while let Ok(s) = tokio::task::block_in_place(|| ac.recv()) {
             sender.start_queue().unwrap();
             let res = match s.as_ref() {
                 NORMAL_SORT_BY_NAME => ok(ws.set_order(FileSortBy::NAME)),
                 NORMAL_SORT_BY_MTIME => ok(ws.set_order(FileSortBy::MTIME)),
                 NORMAL_SORT_BY_SIZE => ok(ws.set_order(FileSortBy::SIZE)),
+                NORMAL_DIR_SIZE => ws.dir_size(false),
+                NORMAL_ALL_DIR_SIZE => ws.dir_size(true),
+                DIR_SIZE_ACTION => ws.dir_size_computed(),
                 NORMAL_MOVE_UP => ok(ws.current_list_mut().move_select(-1)),
                 NORMAL_MOVE_DOWN => ok(ws.current_list_mut().move_select(1)),
                 NORMAL_OPEN_FOLDER => ws.open_selected().await,
@@ -34,10 +44,56 @@ pub async fn init_action(ac: ActionReceiver, mut ws: Workspace, sender: UIEventS
                 NORMAL_TOGGLE_MARK => ok(ws.toggle_mark()),
                 NORMAL_TOGGLE_MARK_ALL => ok(ws.current_list_mut().toggle_mark_all()),
                 NORMAL_NEW_FILE => ws.new_file().await,
-                INPUT_QUIT_ACTION => {
+                NORMAL_NEW_DIR => ws.new_dir().await,
+                NORMAL_RENAME => ws.rename().await,
+                NORMAL_REFRESH => ws.refresh().await,
+                NORMAL_DELETE_FILE => ws.delete(false).await,
+                NORMAL_DELETE_PERMANENTLY => ws.delete(true).await,
+                NORMAL_APPEND_CLIP => ws.append_clip(),
+                NORMAL_CLEAR_CLIP => ws.clear_clip(),
+                NORMAL_PASTE => ws.paste(false).await,
+                NORMAL_MOVE_FILE => ws.paste(true).await,
+                NORMAL_BULK_RENAME => ws.bulk_rename().await,
+                NORMAL_PATTERN_RENAME => ws.pattern_rename().await,
+                NORMAL_OPEN_FILE => ws.open_file().await,
+                NORMAL_OPEN_WITH => ws.open_with().await,
+                NORMAL_CHANGE_MODE => ws.change_attribute(Attribute::Mode).await,
+                NORMAL_CHANGE_OWNER => ws.change_attribute(Attribute::Owner).await,
+                NORMAL_CHANGE_TIMES => ws.change_attribute(Attribute::Times).await,
+                NORMAL_LINK_ABSOLUTE => ws.link(LinkKind::Absolute).await,
+                NORMAL_LINK_RELATIVE => ws.link(LinkKind::Relative).await,
+                NORMAL_HARD_LINK => ws.link(LinkKind::Hard).await,
+                NORMAL_GO_BACK => ws.go_back(false).await,
+                NORMAL_GO_FORWARD => ws.go_back(true).await,
+                NORMAL_SHOW_HISTORY => ws.show_history().await,
+                NORMAL_JUMP_FRECENT => ws.jump_frecent().await,
+                NORMAL_FOLLOW_LINK => ws.follow_link().await,
+                NORMAL_PACK => ws.pack().await,
+                NORMAL_EXTRACT => ws.extract().await,
+                NORMAL_SHELL => ws.shell().await,
+                NORMAL_EDIT => ws.view(true).await,
+                NORMAL_VIEW => ws.view(false).await,
+                NORMAL_UNDO => ws.undo(false),
+                NORMAL_REDO => ws.undo(true),
+                NORMAL_SHOW_TASK_DETAIL => ws.show_task_detail(true),
+                NORMAL_CLOSE_TASK_DETAIL => ws.show_task_detail(false),
+                NORMAL_FAKE_TASK => ws.fake_task(),
+                TASK_CANCEL_ONCE => ws.cancel_task(true).await,
+                TASK_CANCEL => ws.cancel_task(false).await,
+                TASK_DONE_ACTION => ws.refresh_all().await,
+                NORMAL_OPEN_TRASH => ws.open_trash().await,
+                NORMAL_RESTORE_TRASH => ws.restore_trash().await,
+                NORMAL_PURGE_TRASH => ws.purge_trash().await,
+                NORMAL_EMPTY_TRASH => ws.empty_trash().await,
+                NORMAL_OPEN_REVISION => ws.open_revision().await,
+                NORMAL_OPEN_SCRATCH => ws.open_scratch().await,
+                INPUT_QUIT_ACTION | INPUT_ABORT_ACTION => {
                     kbd.switch_to_normal();
                     ok(sender.send(UIEvent::InputQuit).unwrap())
                 }
+                a if a.starts_with(NORMAL_RUN_COMMAND) => {
+                    ws.run_command(&a[NORMAL_RUN_COMMAND.len()..]).await
+                }
                 "Quit" => break,
                 a => ok(log::debug!("unhandled action {}", a)),
             };
@@ -47,9 +103,145 @@ pub async fn init_action(ac: ActionReceiver, mut ws: Workspace, sender: UIEventS
                 log::error!("error {:?}", e);
             }
         }
+        ws.save();
     })
     .await
     .unwrap();
 
     log::debug!("action thread ended");
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::Config;
+    use crate::kbd::init_kbd;
+    use crate::ui::event::EventBody;
+    use crossbeam_channel::{unbounded, Receiver};
+    use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+    use std::fs;
+    use std::path::PathBuf;
+    use std::time::{Duration, Instant};
+
+    const TIMEOUT: Duration = Duration::from_secs(10);
+
+    // the ui events are read in another thread, so the action loop never blocks on the ui
+    fn ui_events(rx: Receiver<EventBody>) -> Receiver<UIEvent> {
+        let (tx, events) = unbounded();
+        std::thread::spawn(move || {
+            for body in rx {
+                let (evs, done) = match body {
+                    EventBody::Single(e, done) => (vec![e], done),
+                    EventBody::Batch(es, done) => (es, done),
+                };
+                if let Some(d) = done {
+                    let _ = d.send(true);
+                }
+                evs.into_iter().for_each(|e| {
+                    let _ = tx.send(e);
+                });
+            }
+        });
+        events
+    }
+
+    fn wait_event(events: &Receiver<UIEvent>, f: impl Fn(&UIEvent) -> bool) {
+        loop {
+            if f(&events.recv_timeout(TIMEOUT).expect("no ui event")) {
+                return;
+            }
+        }
+    }
+
+    fn wait_until(f: impl Fn() -> bool) {
+        let start = Instant::now();
+        while !f() {
+            assert!(start.elapsed() < TIMEOUT, "timed out");
+            std::thread::sleep(Duration::from_millis(20));
+        }
+    }
+
+    fn press(kbd: &Kbd, code: KeyCode) {
+        kbd.press(KeyEvent::new(code, KeyModifiers::NONE));
+    }
+
+    fn type_keys(kbd: &Kbd, keys: &str) {
+        keys.chars().for_each(|c| press(kbd, KeyCode::Char(c)));
+    }
+
+    fn home(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("fff-action-{}-{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(dir.join("work")).unwrap();
+        dir
+    }
+
+    #[tokio::test(threaded_scheduler)]
+    async fn rename_and_undo() {
+        let home = home("rename");
+        let work = home.join("work");
+        fs::write(work.join("a"), "a").unwrap();
+
+        let (sender, rx) = crate::ui::event::UIEventSender::new();
+        let events = ui_events(rx);
+        let (k, ac) = init_kbd(Arc::new(Config::new(&home)), sender.clone());
+        let kbd = Arc::new(k);
+        let mut ws = Workspace::new(work.clone(), home.clone(), sender.clone(), kbd.clone());
+        ws.init().await.unwrap();
+        ws.switch_to(0).await.unwrap();
+        let actions = tokio::spawn(init_action(ac, ws, sender, kbd.clone()));
+
+        tokio::task::spawn_blocking(move || {
+            type_keys(&kbd, "R");
+            wait_event(&events, |e| matches!(e, UIEvent::InputEnter(_)));
+            type_keys(&kbd, "b");
+            press(&kbd, KeyCode::Enter);
+            wait_event(&events, |e| matches!(e, UIEvent::InputQuit));
+            assert!(!work.join("a").exists());
+            assert_eq!(fs::read_to_string(work.join("b")).unwrap(), "a");
+
+            type_keys(&kbd, "z");
+            wait_until(|| work.join("a").exists());
+            assert!(!work.join("b").exists());
+            type_keys(&kbd, "q");
+        })
+        .await
+        .unwrap();
+
+        actions.await.unwrap();
+        fs::remove_dir_all(&home).unwrap();
+    }
+
+    #[tokio::test(threaded_scheduler)]
+    async fn delete_is_undone_at_once() {
+        let home = home("delete");
+        let work = home.join("work");
+        fs::write(work.join("a"), "a").unwrap();
+        fs::write(work.join("b"), "b").unwrap();
+
+        let (sender, rx) = crate::ui::event::UIEventSender::new();
+        let events = ui_events(rx);
+        let (k, ac) = init_kbd(Arc::new(Config::new(&home)), sender.clone());
+        let kbd = Arc::new(k);
+        let mut ws = Workspace::new(work.clone(), home.clone(), sender.clone(), kbd.clone());
+        ws.init().await.unwrap();
+        ws.switch_to(0).await.unwrap();
+        let actions = tokio::spawn(init_action(ac, ws, sender, kbd.clone()));
+
+        tokio::task::spawn_blocking(move || {
+            type_keys(&kbd, "uD");
+            wait_event(&events, |e| matches!(e, UIEvent::InputEnter(_)));
+            type_keys(&kbd, "Y");
+            wait_until(|| !work.join("a").exists() && !work.join("b").exists());
+
+            type_keys(&kbd, "z");
+            wait_until(|| work.join("a").exists() && work.join("b").exists());
+            type_keys(&kbd, "q");
+        })
+        .await
+        .unwrap();
+
+        actions.await.unwrap();
+        fs::remove_dir_all(&home).unwrap();
+    }
+}
@@ -5,7 +5,7 @@ use std::hash::Hash;
 
 macro_rules! create_enum {
     ($name:ident: $($item:ident),*) => {
-        #[derive(PartialEq, Eq, Hash, Debug)]
+        #[derive(PartialEq, Eq, Hash, Debug, Clone, Copy)]
         pub enum $name {
             $($item, )*
         }
@@ -24,6 +24,8 @@ macro_rules! create_enum {
 }
 
 create_enum!(BindingType: All, Normal, Jump, Input, Task, Clip);
+create_enum!(ConflictPolicy: Ask, Overwrite, Skip, Rename, OverwriteIfNewer);
+create_enum!(CommandMode: Foreground, Background, Output);
 create_enum!(
     ColorType: Normal,
     Keyword,
@@ -1,7 +1,7 @@
 #[macro_use]
 pub mod enums;
 
-use crate::config::enums::{BindingType, ColorType};
+use crate::config::enums::{BindingType, ColorType, CommandMode, ConflictPolicy};
 use crossterm::style::Color;
 use std::borrow::Borrow;
 use std::collections::HashMap;
@@ -18,6 +18,24 @@ pub enum Action {
 
 pub type Bindings = HashMap<String, Action>;
 
+// a command in the `commands` section, `run` is a shell line with placeholders
+#[derive(Debug, Clone)]
+pub struct CustomCommand {
+    pub run: String,
+    pub mode: CommandMode,
+}
+
+// a rule of the file opener, a file matches it if it matches all the given conditions
+#[derive(Debug, Clone)]
+pub struct OpenRule {
+    pub ext: Vec<String>,
+    pub mime: Option<String>,
+    pub glob: Option<String>,
+    pub run: String,
+    // run it detached from the terminal instead of in the foreground
+    pub fork: bool,
+}
+
 #[derive(Debug)]
 pub struct Config {
     bindings: HashMap<BindingType, Bindings>,
@@ -25,6 +43,10 @@ pub struct Config {
     pub editor: String,
     pub shell: String,
     pub pager: String,
+    // how to handle an existing target of paste, move and rename
+    pub conflict: ConflictPolicy,
+    pub commands: HashMap<String, CustomCommand>,
+    pub opener: Vec<OpenRule>,
 }
 
 impl Config {
@@ -35,6 +57,9 @@ impl Config {
             editor: "".to_string(),
             shell: "".to_string(),
             pager: "".to_string(),
+            conflict: ConflictPolicy::Ask,
+            commands: HashMap::new(),
+            opener: Vec::new(),
         };
 
         read(&mut c, &DEFAULT);
@@ -91,6 +116,55 @@ fn read_color(config: &mut Config, value: &Value) {
     panic!("color is not a table");
 }
 
+fn read_commands(config: &mut Config, value: &Value) {
+    if let Value::Table(table) = value {
+        for (k, v) in table.iter() {
+            let run = match v.get("run") {
+                Some(r) => read_str(r, "run"),
+                None => panic!("command {} has no run", k),
+            };
+            let mode = v.get("mode").map_or(CommandMode::Foreground, |m| {
+                CommandMode::try_from(read_str(m, "mode").borrow()).unwrap()
+            });
+            config
+                .commands
+                .insert(k.to_string(), CustomCommand { run, mode });
+        }
+        return;
+    }
+    panic!("commands is not a table");
+}
+
+fn read_opener(config: &mut Config, value: &Value) {
+    let rules = match value {
+        Value::Array(a) => a,
+        _ => panic!("opener is not an array"),
+    };
+    let mut re = Vec::new();
+    for r in rules.iter() {
+        let ext = match r.get("ext") {
+            Some(Value::Array(a)) => a.iter().map(|it| read_str(it, "ext")).collect(),
+            Some(e) => vec![read_str(e, "ext")],
+            None => Vec::new(),
+        };
+        re.push(OpenRule {
+            ext,
+            mime: r.get("mime").map(|it| read_str(it, "mime")),
+            glob: r.get("glob").map(|it| read_str(it, "glob")),
+            run: match r.get("run") {
+                Some(v) => read_str(v, "run"),
+                None => panic!("opener rule has no run"),
+            },
+            fork: r
+                .get("fork")
+                .map(|it| it.as_bool().expect("fork is not a bool"))
+                .unwrap_or(false),
+        });
+    }
+    // the rules of the user config go before the default ones
+    config.opener.splice(0..0, re);
+}
+
 fn read_binding(config: &mut Config, value: &Value) {
     if let Value::Table(table) = value {
         for (k, v) in table.iter() {
@@ -148,10 +222,22 @@ fn read(config: &mut Config, content: &str) {
             config.editor = read_str(p, "editor")
         }
 
+        if let Some(p) = table.get("conflict") {
+            config.conflict = ConflictPolicy::try_from(read_str(p, "conflict").borrow()).unwrap();
+        }
+
         if let Some(p) = table.get("color") {
             read_color(config, p);
         }
 
+        if let Some(p) = table.get("commands") {
+            read_commands(config, p);
+        }
+
+        if let Some(p) = table.get("opener") {
+            read_opener(config, p);
+        }
+
         if let Some(p) = table.get("binding") {
             read_binding(config, p);
         }
@@ -1,28 +1,78 @@
-pub const INPUT_QUIT_ACTION: &'static str = "ActionQuitInputMode";
-pub const INPUT_ABORT_ACTION: &'static str = "ActionAbortInputMode";
-pub const INPUT_DELETE_ACTION: &'static str = "ActionInputDelete";
-pub const INPUT_DELETE_BACKWARD_ACTION: &'static str = "ActionInputDeleteBackward";
-pub const INPUT_MOVE_BACK: &'static str = "ActionInputMoveBack";
-pub const INPUT_MOVE_FORWARD: &'static str = "ActionInputMoveForward";
-pub const INPUT_MOVE_TO_START: &'static str = "ActionInputMoveToStart";
-pub const INPUT_MOVE_TO_END: &'static str = "ActionInputMoveToEnd";
+pub const INPUT_QUIT_ACTION: &str = "ActionQuitInputMode";
+pub const INPUT_ABORT_ACTION: &str = "ActionAbortInputMode";
+pub const INPUT_DELETE_ACTION: &str = "ActionInputDelete";
+pub const INPUT_DELETE_BACKWARD_ACTION: &str = "ActionInputDeleteBackward";
+pub const INPUT_MOVE_BACK: &str = "ActionInputMoveBack";
+pub const INPUT_MOVE_FORWARD: &str = "ActionInputMoveForward";
+pub const INPUT_MOVE_TO_START: &str = "ActionInputMoveToStart";
+pub const INPUT_MOVE_TO_END: &str = "ActionInputMoveToEnd";
 
-pub const NORMAL_SORT_BY_NAME: &'static str = "ActionSortByName";
-pub const NORMAL_SORT_BY_MTIME: &'static str = "ActionSortByMtime";
-pub const NORMAL_SORT_BY_SIZE: &'static str = "ActionSortBySize";
-pub const NORMAL_MOVE_UP: &'static str = "ActionMoveUp";
-pub const NORMAL_MOVE_DOWN: &'static str = "ActionMoveDown";
-pub const NORMAL_OPEN_FOLDER: &'static str = "ActionOpenFolderRight";
-pub const NORMAL_CLOSE_FOLDER: &'static str = "ActionCloseFolderRight";
-pub const NORMAL_GROUP_0: &'static str = "ActionChangeGroup0";
-pub const NORMAL_GROUP_1: &'static str = "ActionChangeGroup1";
-pub const NORMAL_GROUP_2: &'static str = "ActionChangeGroup2";
-pub const NORMAL_GROUP_3: &'static str = "ActionChangeGroup3";
-pub const NORMAL_TOGGLE_HIDDEN: &'static str = "ActionToggleHidden";
-pub const NORMAL_TOGGLE_DETAIL: &'static str = "ActionToggleDetail";
-pub const NORMAL_MOVE_FIRST: &'static str = "ActionMoveToFirst";
-pub const NORMAL_MOVE_LAST: &'static str = "ActionMoveToLast";
-pub const NORMAL_TOGGLE_MARK: &'static str = "ActionToggleMark";
-pub const NORMAL_TOGGLE_MARK_ALL: &'static str = "ActionToggleMarkAll";
-pub const NORMAL_NEW_FILE: &'static str = "ActionNewFile";
-pub const NORMAL_NEW_DIR: &'static str = "ActionNewDir";
+// sent by the task manager when a background task is ended
+pub const TASK_DONE_ACTION: &str = "ActionTaskDone";
+// sent by the dir size task when the size of a dir is computed
+pub const DIR_SIZE_ACTION: &str = "ActionDirSizeComputed";
+pub const TASK_CANCEL_ONCE: &str = "ActionCancelTaskOnce";
+pub const TASK_CANCEL: &str = "ActionCancelTask";
+
+pub const NORMAL_SORT_BY_NAME: &str = "ActionSortByName";
+pub const NORMAL_SORT_BY_MTIME: &str = "ActionSortByMtime";
+pub const NORMAL_SORT_BY_SIZE: &str = "ActionSortBySize";
+pub const NORMAL_DIR_SIZE: &str = "ActionDirSize";
+pub const NORMAL_ALL_DIR_SIZE: &str = "ActionAllDirSize";
+pub const NORMAL_MOVE_UP: &str = "ActionMoveUp";
+pub const NORMAL_MOVE_DOWN: &str = "ActionMoveDown";
+pub const NORMAL_OPEN_FOLDER: &str = "ActionOpenFolderRight";
+pub const NORMAL_CLOSE_FOLDER: &str = "ActionCloseFolderRight";
+pub const NORMAL_GROUP_0: &str = "ActionChangeGroup0";
+pub const NORMAL_GROUP_1: &str = "ActionChangeGroup1";
+pub const NORMAL_GROUP_2: &str = "ActionChangeGroup2";
+pub const NORMAL_GROUP_3: &str = "ActionChangeGroup3";
+pub const NORMAL_TOGGLE_HIDDEN: &str = "ActionToggleHidden";
+pub const NORMAL_TOGGLE_DETAIL: &str = "ActionToggleDetail";
+pub const NORMAL_MOVE_FIRST: &str = "ActionMoveToFirst";
+pub const NORMAL_MOVE_LAST: &str = "ActionMoveToLast";
+pub const NORMAL_TOGGLE_MARK: &str = "ActionToggleMark";
+pub const NORMAL_TOGGLE_MARK_ALL: &str = "ActionToggleMarkAll";
+pub const NORMAL_NEW_FILE: &str = "ActionNewFile";
+pub const NORMAL_NEW_DIR: &str = "ActionNewDir";
+pub const NORMAL_RENAME: &str = "ActionRename";
+pub const NORMAL_REFRESH: &str = "ActionRefresh";
+pub const NORMAL_DELETE_FILE: &str = "ActionDeleteFile";
+pub const NORMAL_DELETE_PERMANENTLY: &str = "ActionDeletePermanently";
+pub const NORMAL_APPEND_CLIP: &str = "ActionAppendClip";
+pub const NORMAL_CLEAR_CLIP: &str = "ActionClearClip";
+pub const NORMAL_PASTE: &str = "ActionPaste";
+pub const NORMAL_MOVE_FILE: &str = "ActionMoveFile";
+pub const NORMAL_SHOW_TASK_DETAIL: &str = "ActionShowTaskDetail";
+pub const NORMAL_CLOSE_TASK_DETAIL: &str = "ActionCloseTaskDetail";
+pub const NORMAL_FAKE_TASK: &str = "ActionFakeTask";
+pub const NORMAL_OPEN_TRASH: &str = "ActionOpenTrash";
+pub const NORMAL_RESTORE_TRASH: &str = "ActionRestoreTrash";
+pub const NORMAL_PURGE_TRASH: &str = "ActionPurgeTrash";
+pub const NORMAL_EMPTY_TRASH: &str = "ActionEmptyTrash";
+pub const NORMAL_BULK_RENAME: &str = "ActionBulkRename";
+pub const NORMAL_PATTERN_RENAME: &str = "ActionPatternRename";
+// the prefix of the actions running the custom commands, followed by the command name
+pub const NORMAL_RUN_COMMAND: &str = "ActionCommand:";
+pub const NORMAL_OPEN_FILE: &str = "ActionOpenFile";
+pub const NORMAL_OPEN_WITH: &str = "ActionOpenWith";
+pub const NORMAL_CHANGE_MODE: &str = "ActionChangeMode";
+pub const NORMAL_CHANGE_OWNER: &str = "ActionChangeOwner";
+pub const NORMAL_CHANGE_TIMES: &str = "ActionChangeTimes";
+pub const NORMAL_LINK_ABSOLUTE: &str = "ActionLinkAbsolute";
+pub const NORMAL_LINK_RELATIVE: &str = "ActionLinkRelative";
+pub const NORMAL_HARD_LINK: &str = "ActionHardLink";
+pub const NORMAL_GO_BACK: &str = "ActionGoBack";
+pub const NORMAL_GO_FORWARD: &str = "ActionGoForward";
+pub const NORMAL_SHOW_HISTORY: &str = "ActionShowHistory";
+pub const NORMAL_JUMP_FRECENT: &str = "ActionJumpFrecent";
+pub const NORMAL_FOLLOW_LINK: &str = "ActionFollowLink";
+pub const NORMAL_PACK: &str = "ActionPack";
+pub const NORMAL_EXTRACT: &str = "ActionExtract";
+pub const NORMAL_SHELL: &str = "ActionShell";
+pub const NORMAL_EDIT: &str = "ActionEdit";
+pub const NORMAL_VIEW: &str = "ActionView";
+pub const NORMAL_UNDO: &str = "ActionUndo";
+pub const NORMAL_REDO: &str = "ActionRedo";
+pub const NORMAL_OPEN_REVISION: &str = "ActionOpenRevision";
+pub const NORMAL_OPEN_SCRATCH: &str = "ActionOpenScratch";
@@ -1,22 +1,21 @@
-use crate::config::enums::BindingType;
+use crate::config::enums::{BindingType, ConflictPolicy};
 use crate::config::Config;
 use crate::kbd::action::*;
 use crate::kbd::mode::{KeyCodeAware, KeyEventHandler, Mode};
 use crate::kbd::Answer;
-use crate::ui::event::UIEventSender;
+use crate::ui::event::{UIEvent, UIEventSender};
 use crossbeam_channel::{bounded, Receiver, Sender};
 use crossterm::event::{KeyCode, KeyEvent};
+use unicode_width::UnicodeWidthChar;
 
 pub struct AnswerInput {
     result: Sender<Option<Answer>>,
-    sender: Sender<String>,
     multiple: bool,
 }
 
 impl AnswerInput {
     fn send_result(&mut self, re: Option<Answer>) {
         self.result.send(re).unwrap();
-        self.sender.send(INPUT_QUIT_ACTION.to_string()).unwrap();
     }
 }
 
@@ -43,25 +42,108 @@ impl KeyCodeAware for AnswerInput {
     }
 }
 
+// the answer of a name conflict, the policy and whether to apply it to all the rest conflicts
+pub type Resolution = (ConflictPolicy, bool);
+
+pub struct ResolveInput {
+    result: Sender<Option<Resolution>>,
+}
+
+impl KeyCodeAware for ResolveInput {
+    fn got_key(&mut self, code: &KeyEvent, action: Option<&str>) {
+        if let Some(INPUT_ABORT_ACTION) | Some(INPUT_QUIT_ACTION) = action {
+            self.result.send(None).unwrap();
+            return;
+        }
+
+        if let KeyCode::Char(c) = code.code {
+            let policy = match c.to_ascii_lowercase() {
+                'o' => ConflictPolicy::Overwrite,
+                's' => ConflictPolicy::Skip,
+                'r' => ConflictPolicy::Rename,
+                'n' => ConflictPolicy::OverwriteIfNewer,
+                _ => return,
+            };
+            self.result
+                .send(Some((policy, c.is_ascii_uppercase())))
+                .unwrap();
+        }
+    }
+}
+
+// called with the text whenever it is changed, e.g. to preview what the input does
+pub type OnChange = Box<dyn Fn(&str) + Send>;
+
 pub struct NormalInput {
     result: Sender<Option<String>>,
     sender: Sender<String>,
     ui_event: UIEventSender,
-    input: String,
+    input: Vec<char>,
+    // the index of the char before which the cursor is
     cursor: usize,
+    on_change: Option<OnChange>,
 }
 
 impl NormalInput {
-    fn delete(&mut self, backward: bool) {}
-    fn move_delta(&mut self, delta: i16) {}
-    fn move_to(&mut self, end: bool) {}
-    fn append(&mut self, c: char) {}
+    // the display width of the text before the cursor
+    fn cursor_width(&self) -> usize {
+        self.input[..self.cursor]
+            .iter()
+            .map(|c| c.width().unwrap_or(1))
+            .sum()
+    }
+
+    fn changed(&mut self) {
+        let text: String = self.input.iter().collect();
+        if let Some(f) = &self.on_change {
+            f(&text);
+        }
+        self.ui_event
+            .send(UIEvent::InputUpdate(text, self.cursor_width()))
+            .unwrap();
+    }
+
+    fn moved(&mut self) {
+        self.ui_event
+            .send(UIEvent::InputMove(self.cursor_width()))
+            .unwrap();
+    }
+
+    fn delete(&mut self, backward: bool) {
+        if backward {
+            if self.cursor == 0 {
+                return;
+            }
+            self.cursor -= 1;
+        } else if self.cursor == self.input.len() {
+            return;
+        }
+        self.input.remove(self.cursor);
+        self.changed();
+    }
+
+    fn move_delta(&mut self, delta: i16) {
+        let cursor = (self.cursor as i64 + delta as i64).max(0) as usize;
+        self.cursor = cursor.min(self.input.len());
+        self.moved();
+    }
+
+    fn move_to(&mut self, end: bool) {
+        self.cursor = if end { self.input.len() } else { 0 };
+        self.moved();
+    }
+
+    fn append(&mut self, c: char) {
+        self.input.insert(self.cursor, c);
+        self.cursor += 1;
+        self.changed();
+    }
 }
 
 impl KeyCodeAware for NormalInput {
     fn got_key(&mut self, code: &KeyEvent, action: Option<&str>) {
         match action {
-            Some(INPUT_QUIT_ACTION) => self.result.send(Some(self.input.clone())).unwrap(),
+            Some(INPUT_QUIT_ACTION) => self.result.send(Some(self.input.iter().collect())).unwrap(),
             Some(INPUT_ABORT_ACTION) => self.result.send(None).unwrap(),
             Some(INPUT_DELETE_ACTION) => self.delete(false),
             Some(INPUT_DELETE_BACKWARD_ACTION) => self.delete(true),
@@ -76,10 +158,23 @@ impl KeyCodeAware for NormalInput {
             }
         }
     }
+
+    fn consumes(&self, action: &str) -> bool {
+        matches!(
+            action,
+            INPUT_DELETE_ACTION
+                | INPUT_DELETE_BACKWARD_ACTION
+                | INPUT_MOVE_BACK
+                | INPUT_MOVE_FORWARD
+                | INPUT_MOVE_TO_START
+                | INPUT_MOVE_TO_END
+        )
+    }
 }
 
 pub enum InputMode {
     Answer(Mode<AnswerInput>),
+    Resolve(Mode<ResolveInput>),
     Input(Mode<NormalInput>),
 }
 
@@ -87,6 +182,7 @@ impl InputMode {
     pub fn handle(&mut self, ev: KeyEvent) {
         match self {
             Self::Answer(m) => m.handle(ev),
+            Self::Resolve(m) => m.handle(ev),
             Self::Input(m) => m.handle(ev),
         }
     }
@@ -104,7 +200,6 @@ impl InputMode {
         let (tx, rx) = bounded(0);
         let ai = AnswerInput {
             result: tx,
-            sender: sender.clone(),
             multiple,
         };
 
@@ -119,10 +214,28 @@ impl InputMode {
         )
     }
 
+    pub fn new_resolve(
+        config: &Config,
+        sender: Sender<String>,
+        ui_event: UIEventSender,
+    ) -> (Self, Receiver<Option<Resolution>>) {
+        let (tx, rx) = bounded(0);
+        (
+            InputMode::Resolve(Mode::new(
+                config.bindings(&BindingType::Input),
+                sender,
+                ui_event,
+                ResolveInput { result: tx },
+            )),
+            rx,
+        )
+    }
+
     pub fn new_input(
         config: &Config,
         sender: Sender<String>,
         ui_event: UIEventSender,
+        on_change: Option<OnChange>,
     ) -> (Self, Receiver<Option<String>>) {
         let (tx, rx) = bounded(0);
 
@@ -130,8 +243,9 @@ impl InputMode {
             result: tx,
             sender: sender.clone(),
             ui_event: ui_event.clone(),
-            input: "".to_string(),
+            input: Vec::new(),
             cursor: 0,
+            on_change,
         };
 
         (
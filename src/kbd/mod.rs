@@ -1,12 +1,15 @@
+use crate::config::enums::BindingType;
 use crate::config::Config;
 use crate::kbd::input_mode::InputMode;
+pub use crate::kbd::input_mode::{OnChange, Resolution};
 use crate::kbd::normal_mode::NormalMode;
-use crate::ui::event::UIEventSender;
 use crate::ui::event::UIEvent;
-use crossbeam_channel::{bounded, Receiver, Sender};
-use crossterm::event::{read, Event, KeyEvent};
+use crate::ui::event::UIEventSender;
+use crossbeam_channel::{bounded, select, unbounded, Receiver, RecvError, Sender};
+use crossterm::event::{poll, read, Event, KeyEvent};
 use std::borrow::Borrow;
-use std::sync::{Arc, Mutex};
+use std::sync::{Arc, Condvar, Mutex};
+use std::time::Duration;
 
 pub mod action;
 mod code;
@@ -36,6 +39,63 @@ impl Answer {
     }
 }
 
+const POLL_INTERVAL: Duration = Duration::from_millis(50);
+// the reader parks within a poll interval, this is only reached if it is stuck somehow
+const PARK_TIMEOUT: Duration = Duration::from_secs(2);
+
+// the keyboard reader stops reading when `requested`, and tells it has stopped by `parked`,
+// `running` is false when there is no reader to wait for
+#[derive(Default)]
+struct PauseState {
+    requested: bool,
+    parked: bool,
+    running: bool,
+}
+
+#[derive(Default)]
+struct Pause {
+    state: Mutex<PauseState>,
+    changed: Condvar,
+}
+
+impl Pause {
+    // called by the reader, it waits here while a pause is requested
+    fn park(&self) {
+        let mut st = self.state.lock().unwrap();
+        if !st.requested {
+            return;
+        }
+        st.parked = true;
+        self.changed.notify_all();
+        let mut st = self.changed.wait_while(st, |it| it.requested).unwrap();
+        st.parked = false;
+    }
+
+    fn set_running(&self, running: bool) {
+        self.state.lock().unwrap().running = running;
+        self.changed.notify_all();
+    }
+
+    // request a pause and wait until the reader is parked or gone
+    fn request(&self) {
+        let mut st = self.state.lock().unwrap();
+        st.requested = true;
+        let (st, re) = self
+            .changed
+            .wait_timeout_while(st, PARK_TIMEOUT, |it| it.running && !it.parked)
+            .unwrap();
+        drop(st);
+        if re.timed_out() {
+            log::error!("the keyboard reader is not parked");
+        }
+    }
+
+    fn release(&self) {
+        self.state.lock().unwrap().requested = false;
+        self.changed.notify_all();
+    }
+}
+
 enum ModeEnum {
     Normal(NormalMode),
     Input(InputMode),
@@ -66,45 +126,74 @@ impl ModeEnum {
 
 pub struct Kbd {
     mode: Arc<Mutex<ModeEnum>>,
+    // the extra bindings of the normal mode
+    extra: Mutex<Option<BindingType>>,
     config: Arc<Config>,
     ui_event: UIEventSender,
     sender: Sender<String>,
+    // the actions from outside of the keyboard, it is unbounded so a sender never blocks
+    background: Sender<String>,
+    pause: Arc<Pause>,
 }
 
 impl Kbd {
     pub async fn start(&self) -> i32 {
         let mode = self.mode.clone();
         let s = self.sender.clone();
-        tokio::spawn(async move {
-            loop {
+        let pause = self.pause.clone();
+        tokio::task::spawn_blocking(move || {
+            pause.set_running(true);
+            let re = loop {
+                pause.park();
+                // the reader is the only one sending the keys, so a key read while there is
+                // room is sent without blocking, and a pause is never missed by a blocked send
+                if s.is_full() {
+                    std::thread::sleep(POLL_INTERVAL);
+                    continue;
+                }
+
+                match poll(POLL_INTERVAL) {
+                    Ok(true) => {}
+                    Ok(false) => continue,
+                    Err(_) => break 0,
+                }
                 match read() {
-                    Ok(ev) => {
-                        let mut lock = mode.lock().unwrap();
-                        match ev {
-                            Event::Key(ke) if lock.is_quit(&ke) => {
-                                s.send("Quit".to_string()).unwrap();
-                                break 1;
-                            }
-                            Event::Key(ke) => lock.handle(ke),
-                            _ => (),
-                        };
+                    Ok(Event::Key(ke)) => {
+                        if handle_key(&mode, &s, ke) {
+                            break 1;
+                        }
                     }
+                    Ok(_) => (),
                     _ => break 0,
                 }
-            }
+            };
+            pause.set_running(false);
+            re
         })
         .await
         .unwrap()
     }
 
     pub async fn request_input(&self, prompt: &str) -> Option<String> {
+        self.request_live_input(prompt, None).await
+    }
+
+    // request an input, `on_change` is called with the text while it is typed
+    pub async fn request_live_input(
+        &self,
+        prompt: &str,
+        on_change: Option<OnChange>,
+    ) -> Option<String> {
         let (mode, rx) = InputMode::new_input(
             self.config.borrow(),
             self.sender.clone(),
             self.ui_event.clone(),
+            on_change,
         );
         self.set_mode(ModeEnum::Input(mode));
-        self.ui_event.send(UIEvent::InputEnter(prompt.to_string())).unwrap();
+        self.ui_event
+            .send(UIEvent::InputEnter(prompt.to_string()))
+            .unwrap();
         tokio::spawn(async move { rx.recv().unwrap() })
             .await
             .unwrap()
@@ -118,43 +207,199 @@ impl Kbd {
             multiple,
         );
         self.set_mode(ModeEnum::Input(mode));
-        self.ui_event.send(UIEvent::InputEnter(prompt.to_string())).unwrap();
-        tokio::spawn(async move { rx.recv().unwrap() })
+        let desc = Answer::desc(multiple).join("/");
+        self.ui_event
+            .send(UIEvent::InputEnter(format!("{} ({})", prompt, desc)))
+            .unwrap();
+        let re = tokio::spawn(async move { rx.recv().unwrap() })
             .await
-            .unwrap()
+            .unwrap();
+
+        // answers are usually asked one by one in a single action, so the mode is restored
+        // here instead of queueing a quit action for each of them
+        self.switch_to_normal();
+        self.ui_event.send(UIEvent::InputQuit).unwrap();
+        re
+    }
+
+    pub async fn request_resolution(&self, prompt: &str) -> Option<Resolution> {
+        let (mode, rx) = InputMode::new_resolve(
+            self.config.borrow(),
+            self.sender.clone(),
+            self.ui_event.clone(),
+        );
+        self.set_mode(ModeEnum::Input(mode));
+        self.ui_event
+            .send(UIEvent::InputEnter(format!(
+                "{} ([o]verwrite/[s]kip/[r]ename/overwrite if [n]ewer, upper case for all)",
+                prompt
+            )))
+            .unwrap();
+        let re = tokio::spawn(async move { rx.recv().unwrap() })
+            .await
+            .unwrap();
+
+        self.switch_to_normal();
+        self.ui_event.send(UIEvent::InputQuit).unwrap();
+        re
+    }
+
+    // hand the terminal to an external program, the keyboard is not read and the ui is not
+    // drawn until `resume`
+    pub fn suspend(&self) {
+        self.pause.request();
+        self.ui_event.send_sync(UIEvent::Suspend).unwrap();
+    }
+
+    pub fn resume(&self) {
+        self.ui_event.send_sync(UIEvent::Resume).unwrap();
+        self.pause.release();
+    }
+
+    pub fn config(&self) -> &Config {
+        &self.config
     }
 
     pub fn switch_to_normal(&self) {
+        let extra = self.extra.lock().unwrap();
         self.set_mode(ModeEnum::Normal(NormalMode::new(
             self.config.borrow(),
             self.sender.clone(),
             self.ui_event.clone(),
+            extra.as_ref(),
         )));
     }
 
+    // switch to the normal mode with the bindings of `extra` added, e.g. the task bindings
+    // while the task detail is shown
+    pub fn set_normal_extra(&self, extra: Option<BindingType>) {
+        *self.extra.lock().unwrap() = extra;
+        self.switch_to_normal();
+    }
+
+    // queue an action from outside of the keyboard, e.g. a finished background task
+    pub fn send_action(&self, action: &str) {
+        if self.background.send(action.to_string()).is_err() {
+            log::error!("action {} is dropped", action);
+        }
+    }
+
+    // show a message from outside of the ui, e.g. a skipped file operation
+    pub fn message(&self, msg: &str) {
+        let ev = UIEvent::Message(msg.to_string());
+        if self.ui_event.send(ev).is_err() {
+            log::error!("message {} is dropped", msg);
+        }
+    }
+
     fn set_mode(&self, mode: ModeEnum) {
         let mut s = self.mode.lock().unwrap();
         *s = mode;
     }
 }
 
-pub struct ActionReceiver(pub Receiver<String>);
+// handle a key by the current mode, true if it quits fff
+fn handle_key(mode: &Mutex<ModeEnum>, s: &Sender<String>, ke: KeyEvent) -> bool {
+    let mut lock = mode.lock().unwrap();
+    if lock.is_quit(&ke) {
+        s.send("Quit".to_string()).unwrap();
+        return true;
+    }
+    lock.handle(ke);
+    false
+}
+
+#[cfg(test)]
+impl Kbd {
+    // handle a key as if it is read from the terminal
+    pub fn press(&self, ke: KeyEvent) -> bool {
+        handle_key(&self.mode, &self.sender, ke)
+    }
+}
+
+pub struct ActionReceiver {
+    keys: Receiver<String>,
+    background: Receiver<String>,
+}
+
+impl ActionReceiver {
+    // wait for the next action from the keyboard or from the background
+    pub fn recv(&self) -> Result<String, RecvError> {
+        select! {
+            recv(self.keys) -> s => s,
+            recv(self.background) -> s => s,
+        }
+    }
+}
 
 pub fn init_kbd(config: Arc<Config>, ui_event: UIEventSender) -> (Kbd, ActionReceiver) {
     let (tx, rx) = bounded(10);
-    let ar = ActionReceiver(rx);
+    let (btx, brx) = unbounded();
+    let ar = ActionReceiver {
+        keys: rx,
+        background: brx,
+    };
     let mode = Arc::new(Mutex::new(ModeEnum::Normal(NormalMode::new(
         config.borrow(),
         tx.clone(),
         ui_event.clone(),
+        None,
     ))));
 
     let kbd = Kbd {
         mode: mode.clone(),
+        extra: Mutex::new(None),
         config,
         ui_event,
         sender: tx,
+        background: btx,
+        pause: Arc::new(Pause::default()),
     };
 
     (kbd, ar)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crossbeam_channel::RecvTimeoutError;
+    use std::thread;
+
+    #[test]
+    fn pause_without_reader() {
+        let pause = Pause::default();
+        pause.request();
+        pause.release();
+
+        pause.set_running(true);
+        pause.set_running(false);
+        pause.request();
+        assert!(!pause.state.lock().unwrap().parked);
+    }
+
+    #[test]
+    fn pause_parks_reader() {
+        let pause = Arc::new(Pause::default());
+        pause.set_running(true);
+        let (tx, rx) = bounded::<()>(0);
+        let p = pause.clone();
+        // polls like the keyboard reader until `tx` is dropped
+        let reader = thread::spawn(move || {
+            while let Err(RecvTimeoutError::Timeout) = rx.recv_timeout(POLL_INTERVAL) {
+                p.park();
+            }
+            p.set_running(false);
+        });
+
+        pause.request();
+        assert!(pause.state.lock().unwrap().parked);
+        pause.release();
+
+        // the reader is gone while the pause is requested
+        drop(tx);
+        reader.join().unwrap();
+        pause.request();
+        assert!(!pause.state.lock().unwrap().running);
+        pause.release();
+    }
+}
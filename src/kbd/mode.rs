@@ -13,6 +13,12 @@ pub trait KeyEventHandler {
 
 pub trait KeyCodeAware {
     fn got_key(&mut self, ev: &KeyEvent, action: Option<&str>);
+
+    // whether the action is done by the mode itself, it is not sent to the action loop,
+    // which may be waiting for the mode, e.g. the editing of an input
+    fn consumes(&self, _action: &str) -> bool {
+        false
+    }
 }
 
 pub struct Mode<T: Sized + KeyCodeAware> {
@@ -65,7 +71,9 @@ impl<T: Sized + KeyCodeAware> KeyEventHandler for Mode<T> {
         if let Some(sub) = &self.subs {
             if sub.contains_key(&code) {
                 let x = sub.get(&code).unwrap();
-                self.sender.send(x.0.to_string()).unwrap();
+                if !self.data.consumes(&x.0) {
+                    self.sender.send(x.0.to_string()).unwrap();
+                }
                 self.data.got_key(&ev, Some(&x.0));
             } else {
                 self.data.got_key(&ev, None);
@@ -78,7 +86,9 @@ impl<T: Sized + KeyCodeAware> KeyEventHandler for Mode<T> {
         match action {
             Some(a) => match a {
                 Action::Normal(s) => {
-                    self.sender.send(s.to_string()).unwrap();
+                    if !self.data.consumes(s) {
+                        self.sender.send(s.to_string()).unwrap();
+                    }
                     self.data.got_key(&ev, Some(s));
                 }
                 Action::Prefixed(m) => {
@@ -14,8 +14,17 @@ impl KeyCodeAware for IgnoreItHandler {
 pub struct NormalMode(Mode<IgnoreItHandler>);
 
 impl NormalMode {
-    pub fn new(config: &Config, sender: Sender<String>, ui_event: UIEventSender) -> Self {
-        let bs = config.bindings(&BindingType::Normal);
+    // the bindings of `extra` are added on top of the normal bindings
+    pub fn new(
+        config: &Config,
+        sender: Sender<String>,
+        ui_event: UIEventSender,
+        extra: Option<&BindingType>,
+    ) -> Self {
+        let mut bs = config.bindings(&BindingType::Normal);
+        if let Some(e) = extra {
+            bs.extend(config.bindings(e));
+        }
         NormalMode(Mode::new(bs, sender, ui_event, IgnoreItHandler()))
     }
 
@@ -37,6 +37,7 @@ async fn main() -> Res<()> {
     let wd = current_dir()?;
     let home = dirs::home_dir().unwrap();
     let c = Arc::new(Config::new(&home));
+    model::file::init();
 
     enable_raw_mode().unwrap();
     execute!(stdout(), EnterAlternateScreen, Clear(ClearType::All), Hide).unwrap();
@@ -55,13 +56,21 @@ async fn main() -> Res<()> {
     ws.init().await.unwrap();
     ws.switch_to(0).await.unwrap();
 
+    let actions = init_action(ac, ws, sender.clone(), kbd.clone());
+    tokio::pin!(actions);
     tokio::select! {
-        _ = init_action(ac, ws, sender.clone(), kbd.clone()) => {},
-        _ = kbd.start() => {}
+        _ = &mut actions => {},
+        quit = kbd.start() => {
+            // the action loop saves the workspace after it handles the quit
+            if quit == 1 {
+                actions.await;
+            }
+        }
     }
 
     execute!(stdout(), Show, LeaveAlternateScreen).unwrap();
     disable_raw_mode().unwrap();
+    model::file::archive::clean_temp();
 
     Ok(())
 }
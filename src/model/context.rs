@@ -1,22 +1,133 @@
-use crate::kbd::{Answer, Kbd};
-use std::sync::Arc;
+use crate::config::enums::ConflictPolicy;
+use crate::config::Config;
+use crate::kbd::{Answer, Kbd, OnChange};
+use crate::model::file::conflict;
+use crate::model::journal::{Journal, Step};
+use crate::model::result::Res;
+use std::path::{Path, PathBuf};
+use std::process::{Command, ExitStatus};
+use std::sync::{Arc, Mutex, MutexGuard};
+
+// the command of a program line in the config, e.g. `code --wait`
+fn command(line: &str) -> Command {
+    let mut words = line.split_whitespace();
+    let mut cmd = Command::new(words.next().unwrap_or_default());
+    cmd.args(words);
+    cmd
+}
 
 pub struct Context {
     kbd: Arc<Kbd>,
+    journal: Mutex<Journal>,
 }
 
 impl Context {
-    pub fn new(kbd: Arc<Kbd>) -> Self {
-        Context { kbd }
+    pub fn new(kbd: Arc<Kbd>, journal: Journal) -> Self {
+        Context {
+            kbd,
+            journal: Mutex::new(journal),
+        }
+    }
+
+    pub fn config(&self) -> &Config {
+        self.kbd.config()
+    }
+
+    pub fn journal(&self) -> MutexGuard<'_, Journal> {
+        self.journal.lock().unwrap()
+    }
+
+    // record the done steps of an operation so it can be undone
+    pub fn record(&self, desc: String, steps: Vec<Step>) {
+        self.journal().record(desc, steps)
+    }
+
+    // notify the action loop from a background task
+    pub fn send_action(&self, action: &str) {
+        self.kbd.send_action(action)
     }
 
     pub async fn request_input(&self, msg: &str) -> Option<String> {
         self.kbd.request_input(msg).await
     }
 
+    pub async fn request_live_input(&self, msg: &str, on_change: OnChange) -> Option<String> {
+        self.kbd.request_live_input(msg, Some(on_change)).await
+    }
+
     pub async fn request_answer(&self, msg: &str, multiple: bool) -> Option<Answer> {
         self.kbd.request_answer(msg, multiple).await
     }
 
-    pub fn message(&self, _msg: &str) {}
+    // the policy for the existing `to`, it is asked if the config says so, `all` keeps
+    // the policy chosen for all the rest conflicts, None if the question is aborted
+    pub async fn conflict_policy(
+        &self,
+        from: &Path,
+        to: &Path,
+        all: &mut Option<ConflictPolicy>,
+    ) -> Option<ConflictPolicy> {
+        if let Some(p) = all {
+            return Some(*p);
+        }
+        if self.kbd.config().conflict != ConflictPolicy::Ask {
+            return Some(self.kbd.config().conflict);
+        }
+
+        let msg = format!("{} exists, {}", to.display(), conflict::compare(from, to));
+        let (p, for_all) = self.kbd.request_resolution(&msg).await?;
+        if for_all {
+            *all = Some(p);
+        }
+        Some(p)
+    }
+
+    // run an external program in the foreground, it owns the terminal until it exits
+    pub async fn run(&self, mut cmd: Command) -> Res<ExitStatus> {
+        let kbd = self.kbd.clone();
+        tokio::task::spawn_blocking(move || {
+            kbd.suspend();
+            let re = cmd.status();
+            kbd.resume();
+            Ok(re?)
+        })
+        .await?
+    }
+
+    // open the files in the configured editor
+    pub async fn edit(&self, paths: &[PathBuf]) -> Res<ExitStatus> {
+        let mut cmd = command(&self.kbd.config().editor);
+        cmd.args(paths);
+        self.run(cmd).await
+    }
+
+    // open the files in the configured pager
+    pub async fn view(&self, paths: &[PathBuf]) -> Res<ExitStatus> {
+        let mut cmd = command(&self.kbd.config().pager);
+        cmd.args(paths);
+        self.run(cmd).await
+    }
+
+    // run the configured shell in `dir`
+    pub async fn shell(&self, dir: &Path, env: &[(String, String)]) -> Res<ExitStatus> {
+        let mut cmd = command(&self.kbd.config().shell);
+        cmd.current_dir(dir);
+        cmd.envs(env.iter().map(|(k, v)| (k, v)));
+        self.run(cmd).await
+    }
+
+    pub fn message(&self, msg: &str) {
+        self.kbd.message(msg)
+    }
+}
+
+#[cfg(test)]
+impl Context {
+    // a context without the ui, the config is the default one if `home` has none
+    pub fn for_test(home: &Path) -> Context {
+        let (ui_event, _) = crate::ui::event::UIEventSender::new();
+        let config = Arc::new(Config::new(&home.to_path_buf()));
+        let (kbd, _) = crate::kbd::init_kbd(config, ui_event);
+        Context::new(Arc::new(kbd), Journal::new(home))
+    }
 }
@@ -0,0 +1,317 @@
+// A revision of a git repository is browsed like a read-only archive, the tree is listed
+// and the blobs are read by calling the local `git` binary.
+use crate::model::file::archive::{create, Archive, Entry, Index};
+use crate::model::file::path::InnerPath;
+use crate::model::file::protocol::Protocol;
+use crate::model::file::{FileInfo, InnerFile, LinkInfo, ProtocolInfo};
+use crate::model::result::{Error, Res, Void};
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, UNIX_EPOCH};
+
+const NAME: &str = "git";
+const HASH_LEN: usize = 40;
+
+pub struct GitProtocol {
+    // resolved revisions, (repository, revision) -> commit, only the full hashes are kept, as
+    // the refs like HEAD or a branch may point to another commit later
+    revisions: Mutex<HashMap<(PathBuf, String), String>>,
+    // loaded trees, (repository, commit) -> tree
+    trees: Mutex<HashMap<(PathBuf, String), Arc<Tree>>>,
+}
+
+impl GitProtocol {
+    pub fn new() -> Self {
+        GitProtocol {
+            revisions: Mutex::new(HashMap::new()),
+            trees: Mutex::new(HashMap::new()),
+        }
+    }
+
+    // split a protocol path `<rev>/path` to the revision and the path in the tree,
+    // the revision itself can contain `/`, so the longest known or valid prefix is used.
+    fn split(&self, repo: &Path, path: &Path) -> Res<(String, String, PathBuf)> {
+        let parts: Vec<_> = path
+            .components()
+            .map(|it| it.as_os_str().to_string_lossy().to_string())
+            .filter(|it| it != "/")
+            .collect();
+
+        let known = {
+            let rs = self.revisions.lock().unwrap();
+            (1..=parts.len()).rev().find_map(|i| {
+                let rev = parts[..i].join("/");
+                rs.get(&(repo.to_path_buf(), rev.clone()))
+                    .map(|c| (i, rev, c.clone()))
+            })
+        };
+
+        let (i, rev, commit) = match known {
+            Some(v) => v,
+            None => (1..=parts.len())
+                .find_map(|i| {
+                    let rev = parts[..i].join("/");
+                    resolve(repo, &rev).ok().map(|c| (i, rev, c))
+                })
+                .ok_or_else(|| Error::PathNotExists(path.display().to_string()))?,
+        };
+
+        if is_full_hash(&rev) {
+            self.revisions
+                .lock()
+                .unwrap()
+                .insert((repo.to_path_buf(), rev.clone()), commit.clone());
+        }
+        Ok((rev, commit, Index::normalize(&parts[i..].join("/"))))
+    }
+
+    fn revision(&self, repo: &Path, rev: &str, commit: &str) -> Res<Arc<dyn Archive>> {
+        let key = (repo.to_path_buf(), commit.to_string());
+        let mut ts = self.trees.lock().unwrap();
+        let tree = match ts.get(&key) {
+            Some(t) => t.clone(),
+            None => {
+                let t = Arc::new(Tree::load(repo, commit)?);
+                ts.insert(key, t.clone());
+                t
+            }
+        };
+
+        Ok(Arc::new(Revision {
+            rev: rev.to_string(),
+            tree,
+        }))
+    }
+}
+
+impl Protocol for GitProtocol {
+    fn name(&self) -> &'static str {
+        NAME
+    }
+
+    // revisions are opened by path, not by entering a file
+    fn support(&self, _: &InnerFile) -> bool {
+        false
+    }
+
+    fn create(&self, info: ProtocolInfo, path: &Path) -> Res<InnerFile> {
+        let repo = info.root.path.clone();
+        let (rev, commit, p) = self.split(&repo, path)?;
+        create(info, &p, self.revision(&repo, &rev, &commit)?)
+    }
+
+    fn parent(&self, info: &FileInfo) -> Option<PathBuf> {
+        let repo = &info.protocol.as_ref()?.root.path;
+        let (rev, _, p) = self.split(repo, &info.path).ok()?;
+        p.parent().map(|it| join_revision(&rev, it))
+    }
+}
+
+fn is_full_hash(rev: &str) -> bool {
+    rev.len() == HASH_LEN && rev.chars().all(|it| it.is_ascii_hexdigit())
+}
+
+fn join_revision(rev: &str, path: &Path) -> PathBuf {
+    match path.strip_prefix("/") {
+        Ok(p) if p.as_os_str().is_empty() => PathBuf::from(rev),
+        Ok(p) => Path::new(rev).join(p),
+        Err(_) => Path::new(rev).join(path),
+    }
+}
+
+// the protocol path to browse the revision `rev` of the repository which contains `dir`
+pub fn revision_path(dir: &Path, rev: &str) -> Res<InnerPath> {
+    let top = git(dir, &["rev-parse", "--show-toplevel"])?;
+    let prefix = git(dir, &["rev-parse", "--show-prefix"])?;
+    resolve(Path::new(top.trim()), rev)?;
+
+    let p = Path::new(rev).join(prefix.trim());
+    Ok(InnerPath::new_protocol(Path::new(top.trim()), NAME, &p))
+}
+
+fn git(repo: &Path, args: &[&str]) -> Res<String> {
+    let out = Command::new("git")
+        .arg("-C")
+        .arg(repo)
+        .args(args)
+        .stdin(Stdio::null())
+        .output()?;
+
+    if !out.status.success() {
+        return Err(Error::CommandFailed(
+            String::from_utf8_lossy(&out.stderr).trim().to_string(),
+        ));
+    }
+    Ok(String::from_utf8_lossy(&out.stdout).to_string())
+}
+
+fn resolve(repo: &Path, rev: &str) -> Res<String> {
+    let c = git(
+        repo,
+        &[
+            "rev-parse",
+            "--verify",
+            "--quiet",
+            &format!("{}^{{commit}}", rev),
+        ],
+    )?;
+    Ok(c.trim().to_string())
+}
+
+struct Tree {
+    repo: PathBuf,
+    index: Index,
+}
+
+impl Tree {
+    fn load(repo: &Path, commit: &str) -> Res<Self> {
+        let time = git(repo, &["log", "-1", "--format=%ct", commit])?
+            .trim()
+            .parse::<u64>()
+            .ok()
+            .map(|it| UNIX_EPOCH + Duration::from_secs(it));
+        let out = git(repo, &["ls-tree", "-r", "-t", "-l", "-z", commit])?;
+
+        let mut index = Index::new();
+        let mut links = Vec::new();
+        for line in out.split('\0').filter(|it| !it.is_empty()) {
+            let (meta, name) = match line.find('\t') {
+                Some(i) => (&line[..i], &line[i + 1..]),
+                None => continue,
+            };
+            let ms: Vec<_> = meta.split_whitespace().collect();
+            if ms.len() != 4 {
+                continue;
+            }
+
+            let mode = u32::from_str_radix(ms[0], 8).unwrap_or(0);
+            if ms[1] == "blob" && mode & 0o170000 == 0o120000 {
+                links.push((Index::normalize(name), ms[2].to_string()));
+            }
+            index.insert(
+                name,
+                Entry {
+                    raw: ms[2].to_string(),
+                    size: ms[3].parse().unwrap_or(0),
+                    mode,
+                    modified: time,
+                    is_dir: ms[1] == "tree",
+                    link: None,
+                },
+            );
+        }
+
+        let targets = cat_blobs(repo, links.iter().map(|it| it.1.as_ref()).collect())?;
+        links.into_iter().zip(targets).for_each(|((p, _), t)| {
+            let target = Index::normalize(&p.parent().unwrap_or(&p).join(t).display().to_string());
+            let broken = index.get(&target).is_none();
+            if let Some(e) = index.get_mut(&p) {
+                e.link = Some(LinkInfo {
+                    broken,
+                    target: target.display().to_string(),
+                });
+            }
+        });
+
+        Ok(Tree {
+            repo: repo.to_path_buf(),
+            index,
+        })
+    }
+}
+
+// read the content of multiple blobs with one `git cat-file --batch` call
+fn cat_blobs(repo: &Path, objects: Vec<&str>) -> Res<Vec<String>> {
+    if objects.is_empty() {
+        return Ok(Vec::new());
+    }
+
+    let mut child = Command::new("git")
+        .arg("-C")
+        .arg(repo)
+        .args(["cat-file", "--batch"])
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .spawn()?;
+    // write in another thread, or the child may be blocked by a full stdout pipe
+    let input = format!("{}\n", objects.join("\n"));
+    let writer = child
+        .stdin
+        .take()
+        .map(|mut stdin| std::thread::spawn(move || stdin.write_all(input.as_bytes())));
+    let out = child.wait_with_output()?.stdout;
+    if let Some(w) = writer {
+        w.join().unwrap()?;
+    }
+
+    // every object is "<sha> <type> <size>\n<content>\n"
+    let mut re = Vec::new();
+    let mut i = 0;
+    while i < out.len() && re.len() < objects.len() {
+        let end = match out[i..].iter().position(|it| *it == b'\n') {
+            Some(p) => i + p,
+            None => break,
+        };
+        let header = String::from_utf8_lossy(&out[i..end]).to_string();
+        let size = header
+            .split(' ')
+            .nth(2)
+            .and_then(|it| it.parse::<usize>().ok())
+            .unwrap_or(0);
+        let start = end + 1;
+        let stop = std::cmp::min(start + size, out.len());
+        re.push(String::from_utf8_lossy(&out[start..stop]).to_string());
+        i = stop + 1;
+    }
+    Ok(re)
+}
+
+// a loaded tree named by the revision string used to open it
+struct Revision {
+    rev: String,
+    tree: Arc<Tree>,
+}
+
+impl Archive for Revision {
+    fn index(&self) -> &Index {
+        &self.tree.index
+    }
+
+    fn extract(&self, path: &Path, to: &Path) -> Void {
+        let object = self.tree.index.content(path)?.1.raw.clone();
+        let out = Command::new("git")
+            .arg("-C")
+            .arg(&self.tree.repo)
+            .args(["cat-file", "blob", &object])
+            .stdin(Stdio::null())
+            .output()?;
+        if !out.status.success() {
+            return Err(Error::CommandFailed(
+                String::from_utf8_lossy(&out.stderr).trim().to_string(),
+            ));
+        }
+        File::create(to)?.write_all(&out.stdout)?;
+        Ok(())
+    }
+
+    fn inner_path(&self, path: &Path) -> PathBuf {
+        join_revision(&self.rev, path)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn full_hashes() {
+        assert!(is_full_hash("0123456789abcdef0123456789ABCDEF01234567"));
+        assert!(!is_full_hash("0123456"));
+        assert!(!is_full_hash("HEAD"));
+        assert!(!is_full_hash("0123456789abcdef0123456789abcdef0123456g"));
+    }
+}
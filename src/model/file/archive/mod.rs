@@ -0,0 +1,465 @@
+use crate::model::context::Context;
+use crate::model::file::path::InnerPath;
+use crate::model::file::{local, protocol};
+use crate::model::file::{DirOp, FileInfo, FileOp, InnerFile, LinkInfo, Op, ProtocolInfo};
+use crate::model::result::{Error, Res, Void};
+use async_trait::async_trait;
+use lazy_static::lazy_static;
+use std::collections::HashMap;
+use std::convert::TryFrom;
+use std::fs::{create_dir_all, remove_dir_all, DirBuilder};
+use std::io::ErrorKind;
+use std::os::unix::fs::DirBuilderExt;
+use std::path::{Component, Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+pub mod git;
+pub mod pack;
+pub mod tar;
+pub mod zip;
+
+pub const DEFAULT_FILE_MODE: u32 = 0o100644;
+pub const DEFAULT_DIR_MODE: u32 = 0o040755;
+const TEMP_ATTEMPTS: u64 = 16;
+// the symbolic links followed at most to resolve a path, as the ELOOP limit of linux
+const MAX_LINKS: usize = 40;
+const TYPE_MASK: u32 = 0o170000;
+// spreads the names tried for the temp dir
+const PRIME: u64 = 0x9e37_79b9_7f4a_7c15;
+
+lazy_static! {
+    static ref TEMP_DIR: Mutex<Option<PathBuf>> = Mutex::new(None);
+}
+
+pub struct Entry {
+    // the entry name used by the archive itself, empty for the dirs created implicitly
+    pub raw: String,
+    pub size: u64,
+    pub mode: u32,
+    pub modified: Option<SystemTime>,
+    pub is_dir: bool,
+    pub link: Option<LinkInfo>,
+}
+
+impl Entry {
+    fn implicit_dir() -> Self {
+        Entry {
+            raw: "".to_string(),
+            size: 0,
+            mode: DEFAULT_DIR_MODE,
+            modified: None,
+            is_dir: true,
+            link: None,
+        }
+    }
+}
+
+// all the entries of an archive, keyed by the absolute path in it
+pub struct Index {
+    entries: HashMap<PathBuf, Entry>,
+    children: HashMap<PathBuf, Vec<PathBuf>>,
+}
+
+impl Index {
+    pub fn new() -> Self {
+        let mut idx = Index {
+            entries: HashMap::new(),
+            children: HashMap::new(),
+        };
+        idx.entries
+            .insert(PathBuf::from("/"), Entry::implicit_dir());
+        idx
+    }
+
+    // "./a/b/", "a/b" and "/a/b" are all normalized to "/a/b"
+    pub fn normalize(name: &str) -> PathBuf {
+        let mut p = PathBuf::from("/");
+        Path::new(name).components().for_each(|it| match it {
+            Component::Normal(n) => p.push(n),
+            Component::ParentDir => {
+                p.pop();
+            }
+            _ => {}
+        });
+        p
+    }
+
+    pub fn insert(&mut self, name: &str, entry: Entry) {
+        let path = Index::normalize(name);
+        self.ensure_parent(&path);
+        if !self.entries.contains_key(&path) {
+            if let Some(p) = path.parent() {
+                self.children
+                    .entry(p.to_path_buf())
+                    .or_default()
+                    .push(path.clone());
+            }
+        }
+        self.entries.insert(path, entry);
+    }
+
+    fn ensure_parent(&mut self, path: &Path) {
+        if let Some(p) = path.parent() {
+            if !self.entries.contains_key(p) {
+                self.insert(&p.display().to_string(), Entry::implicit_dir());
+            }
+        }
+    }
+
+    pub fn get(&self, path: &Path) -> Option<&Entry> {
+        self.entries.get(path)
+    }
+
+    pub fn get_mut(&mut self, path: &Path) -> Option<&mut Entry> {
+        self.entries.get_mut(path)
+    }
+
+    pub fn children(&self, path: &Path) -> Vec<&PathBuf> {
+        self.children
+            .get(path)
+            .map_or_else(Vec::new, |it| it.iter().collect())
+    }
+
+    // the entry at `path` itself, the symbolic links in its parents are followed
+    pub fn lookup(&self, path: &Path) -> Option<(PathBuf, &Entry)> {
+        let p = match (path.parent(), path.file_name()) {
+            (Some(parent), Some(name)) => self.resolve(parent)?.join(name),
+            _ => path.to_path_buf(),
+        };
+        self.entries.get(&p).map(|it| (p, it))
+    }
+
+    // the path of the entry `path` points to, all the symbolic links in it are followed, None
+    // if it is not in the archive
+    pub fn resolve(&self, path: &Path) -> Option<PathBuf> {
+        let mut p = PathBuf::from("/");
+        let mut followed = 0;
+        for c in path.components() {
+            if let Component::Normal(n) = c {
+                p.push(n);
+            }
+            while let Some(Entry { link: Some(l), .. }) = self.entries.get(&p) {
+                if l.broken || followed == MAX_LINKS {
+                    return None;
+                }
+                followed += 1;
+                p = PathBuf::from(&l.target);
+            }
+        }
+        Some(p).filter(|it| self.entries.contains_key(it))
+    }
+
+    // the regular file holding the content of `path`, a link to a dir, to a special file or
+    // out of the archive has no content to extract
+    pub fn content(&self, path: &Path) -> Res<(PathBuf, &Entry)> {
+        let (p, e) = self
+            .resolve(path)
+            .and_then(|it| self.entries.get(&it).map(|e| (it, e)))
+            .ok_or_else(|| Error::PathNotExists(path.display().to_string()))?;
+        match e.mode & TYPE_MASK {
+            _ if e.is_dir => Err(Error::SpecialFile(path.display().to_string())),
+            0 | 0o100000 => Ok((p, e)),
+            _ => Err(Error::SpecialFile(path.display().to_string())),
+        }
+    }
+}
+
+pub trait Archive: Send + Sync {
+    fn index(&self) -> &Index;
+    // write the content of the entry at `path` to the file `to`
+    fn extract(&self, path: &Path, to: &Path) -> Void;
+    // the path shown in `FileInfo` for the entry at `path`
+    fn inner_path(&self, path: &Path) -> PathBuf {
+        path.to_path_buf()
+    }
+}
+
+type Loaded = (Option<SystemTime>, Arc<dyn Archive>);
+
+// loaded archives of a protocol, keyed by the host file and reloaded when it is changed
+pub struct Cache(Mutex<HashMap<PathBuf, Loaded>>);
+
+impl Cache {
+    pub fn new() -> Self {
+        Cache(Mutex::new(HashMap::new()))
+    }
+
+    pub fn get<F>(&self, info: &ProtocolInfo, load: F) -> Res<Arc<dyn Archive>>
+    where
+        F: FnOnce(&Path) -> Res<Arc<dyn Archive>>,
+    {
+        let mut map = self.0.lock().unwrap();
+        let modified = info.root.modified;
+        if let Some((m, a)) = map.get(&info.root.path) {
+            if m == &modified {
+                return Ok(a.clone());
+            }
+        }
+
+        let a = load(&info.root.path)?;
+        map.insert(info.root.path.clone(), (modified, a.clone()));
+        Ok(a)
+    }
+}
+
+// the dir of this process only, the files are extracted into it to be opened, and the other
+// temporary files of fff are written into it, it is created with a random name, so it is
+// neither guessed nor shared by another process
+pub fn temp_dir() -> Res<PathBuf> {
+    let mut dir = TEMP_DIR.lock().unwrap();
+    if let Some(d) = dir.as_ref() {
+        return Ok(d.clone());
+    }
+
+    let seed = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|it| it.as_nanos() as u64)
+        .unwrap_or(0);
+    for i in 0..TEMP_ATTEMPTS {
+        let suffix = seed.wrapping_add(i).wrapping_mul(PRIME);
+        let name = format!("fff-{}-{:x}", std::process::id(), suffix);
+        let p = std::env::temp_dir().join(name);
+        match DirBuilder::new().mode(0o700).create(&p) {
+            Ok(_) => {
+                *dir = Some(p.clone());
+                return Ok(p);
+            }
+            Err(e) if e.kind() == ErrorKind::AlreadyExists => continue,
+            Err(e) => return Err(e.into()),
+        }
+    }
+    Err(Error::Io(ErrorKind::AlreadyExists))
+}
+
+// remove the extracted files when fff exits
+pub fn clean_temp() {
+    if let Some(d) = TEMP_DIR.lock().unwrap().take() {
+        if let Err(e) = remove_dir_all(&d) {
+            log::error!("remove {} failed: {:?}", d.display(), e);
+        }
+    }
+}
+
+pub fn create(info: ProtocolInfo, path: &Path, archive: Arc<dyn Archive>) -> Res<InnerFile> {
+    let index = archive.index();
+    let (real, entry) = match index.lookup(path) {
+        Some(v) => v,
+        None => {
+            return Err(Error::PathNotExists(
+                InnerPath::new_protocol(&info.root.path, &info.protocol, path).to_string(),
+            ))
+        }
+    };
+    // a link to a dir is shown as a dir like the local ones, its children are listed under it
+    let target = index.resolve(&real).and_then(|it| index.get(&it));
+    let is_dir = entry.is_dir || (entry.link.is_some() && matches!(target, Some(t) if t.is_dir));
+
+    let mut fi = protocol::file_info(&info, &archive.inner_path(path), is_dir);
+    fi.size = entry.size;
+    fi.mode = entry.mode;
+    fi.modified = entry.modified;
+    fi.link = entry.link.clone();
+
+    let entry = path.to_path_buf();
+    Ok(if fi.is_dir {
+        InnerFile::Dir(Box::new(ArchiveDir {
+            info: fi,
+            entry,
+            archive,
+        }))
+    } else {
+        InnerFile::File(Box::new(ArchiveFile {
+            info: fi,
+            entry,
+            archive,
+        }))
+    })
+}
+
+fn protocol_info(info: &FileInfo) -> &ProtocolInfo {
+    info.protocol.as_ref().unwrap()
+}
+
+fn read_only(info: &FileInfo) -> Error {
+    Error::ReadOnly(info.inner.to_string())
+}
+
+pub struct ArchiveDir {
+    info: FileInfo,
+    entry: PathBuf,
+    archive: Arc<dyn Archive>,
+}
+
+#[async_trait]
+impl Op for ArchiveDir {
+    fn get(&self) -> &FileInfo {
+        &self.info
+    }
+    async fn parent(&self, _: &Context) -> Res<InnerFile> {
+        protocol::parent(&self.info)
+    }
+    async fn rename(&self, _: &Context) -> Void {
+        Err(read_only(&self.info))
+    }
+    async fn delete(&self, _: &Context) -> Void {
+        Err(read_only(&self.info))
+    }
+    async fn delete_permanently(&self, _: &Context) -> Void {
+        Err(read_only(&self.info))
+    }
+    async fn open(&self, _: &Context) -> Void {
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl DirOp for ArchiveDir {
+    async fn list(&self, _: &Context) -> Res<Vec<InnerFile>> {
+        let pi = protocol_info(&self.info);
+        let index = self.archive.index();
+        let real = match index.resolve(&self.entry) {
+            Some(v) => v,
+            None => return Ok(Vec::new()),
+        };
+        index
+            .children(&real)
+            .into_iter()
+            .filter_map(|it| it.file_name())
+            .map(|it| create(pi.clone(), &self.entry.join(it), self.archive.clone()))
+            .collect()
+    }
+
+    async fn new_file(&self, _: &Context) -> Void {
+        Err(read_only(&self.info))
+    }
+
+    async fn new_dir(&self, _: &Context) -> Void {
+        Err(read_only(&self.info))
+    }
+
+    async fn goto(&self, _: &Context, child_path: &str) -> Res<InnerFile> {
+        let p = Index::normalize(&self.entry.join(child_path).display().to_string());
+        create(protocol_info(&self.info).clone(), &p, self.archive.clone())
+    }
+
+    async fn shell(&self, _: &Context, _: &[(String, String)]) -> Void {
+        Ok(())
+    }
+}
+
+pub struct ArchiveFile {
+    info: FileInfo,
+    entry: PathBuf,
+    archive: Arc<dyn Archive>,
+}
+
+impl ArchiveFile {
+    // extract the file to the temp dir, so it can be handled like a local file
+    fn extract_temp(&self) -> Res<InnerFile> {
+        let pi = protocol_info(&self.info);
+        let to = temp_dir()?
+            .join(format!("{}-{}", pi.protocol, pi.instance_id))
+            .join(self.info.path.strip_prefix("/").unwrap_or(&self.info.path));
+        if let Some(p) = to.parent() {
+            create_dir_all(p)?;
+        }
+        self.archive.extract(&self.entry, &to)?;
+        local::make(InnerPath::try_from(to)?)
+    }
+}
+
+#[async_trait]
+impl Op for ArchiveFile {
+    fn get(&self) -> &FileInfo {
+        &self.info
+    }
+    async fn parent(&self, _: &Context) -> Res<InnerFile> {
+        protocol::parent(&self.info)
+    }
+    async fn rename(&self, _: &Context) -> Void {
+        Err(read_only(&self.info))
+    }
+    async fn delete(&self, _: &Context) -> Void {
+        Err(read_only(&self.info))
+    }
+    async fn delete_permanently(&self, _: &Context) -> Void {
+        Err(read_only(&self.info))
+    }
+    async fn open(&self, ctx: &Context) -> Void {
+        self.extract_temp()?.open(ctx).await
+    }
+}
+
+#[async_trait]
+impl FileOp for ArchiveFile {
+    async fn view(&self, ctx: &Context) -> Void {
+        match self.extract_temp()? {
+            InnerFile::File(f) => f.view(ctx).await,
+            _ => Ok(()),
+        }
+    }
+    async fn edit(&self, ctx: &Context) -> Void {
+        match self.extract_temp()? {
+            InnerFile::File(f) => f.edit(ctx).await,
+            _ => Ok(()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn file(size: u64) -> Entry {
+        Entry {
+            raw: "".to_string(),
+            size,
+            mode: DEFAULT_FILE_MODE,
+            modified: None,
+            is_dir: false,
+            link: None,
+        }
+    }
+
+    #[test]
+    fn normalize_names() {
+        let expected = PathBuf::from("/a/b");
+        assert_eq!(Index::normalize("a/b"), expected);
+        assert_eq!(Index::normalize("./a/b/"), expected);
+        assert_eq!(Index::normalize("/a/b"), expected);
+        assert_eq!(Index::normalize("a/c/../b"), expected);
+        assert_eq!(Index::normalize("../../a/b"), expected);
+        assert_eq!(Index::normalize(""), PathBuf::from("/"));
+    }
+
+    #[test]
+    fn insert_creates_parents() {
+        let mut idx = Index::new();
+        idx.insert("a/b/c.txt", file(3));
+
+        assert!(idx.get(Path::new("/a")).unwrap().is_dir);
+        assert!(idx.get(Path::new("/a/b")).unwrap().is_dir);
+        assert_eq!(idx.get(Path::new("/a/b/c.txt")).unwrap().size, 3);
+        assert_eq!(idx.children(Path::new("/")), vec![&PathBuf::from("/a")]);
+        assert_eq!(
+            idx.children(Path::new("/a/b")),
+            vec![&PathBuf::from("/a/b/c.txt")]
+        );
+    }
+
+    #[test]
+    fn insert_replaces_implicit_dir() {
+        let mut idx = Index::new();
+        idx.insert("a/b.txt", file(1));
+        let mut dir = Entry::implicit_dir();
+        dir.raw = "a/".to_string();
+        idx.insert("a/", dir);
+        idx.insert("a/b.txt", file(2));
+
+        assert_eq!(idx.get(Path::new("/a")).unwrap().raw, "a/");
+        assert_eq!(idx.get(Path::new("/a/b.txt")).unwrap().size, 2);
+        assert_eq!(idx.children(Path::new("/")).len(), 1);
+        assert_eq!(idx.children(Path::new("/a")).len(), 1);
+    }
+}
@@ -0,0 +1,468 @@
+// Create the archives of local files and extract them. The format is chosen by the name of the
+// archive, zip, tar.gz and tar.zst can be created, and all the formats browsable can be extracted.
+use crate::model::file::archive::tar::Compression;
+use crate::model::result::{Error, Res, Void};
+use crate::model::task::Progress;
+use chrono::{DateTime, Datelike, Local, Timelike};
+use flate2::write::GzEncoder;
+use std::collections::BTreeSet;
+use std::fs::{self, File, Metadata, OpenOptions, Permissions};
+use std::io::{self, Read, Write};
+use std::os::unix::fs::{OpenOptionsExt, PermissionsExt};
+use std::path::{Component, Path, PathBuf};
+use std::sync::Arc;
+use tar::Builder;
+use zip::write::FileOptions;
+use zip::{CompressionMethod, ZipArchive, ZipWriter};
+
+pub const CREATE_EXTENSIONS: [&str; 3] = [".zip", ".tar.gz", ".tar.zst"];
+const ZIP_EXTENSIONS: [&str; 5] = [".zip", ".jar", ".war", ".apk", ".whl"];
+const ZSTD_LEVEL: i32 = 3;
+const S_IFMT: u32 = 0o170000;
+const S_IFLNK: u32 = 0o120000;
+
+enum Format {
+    Zip,
+    Tar,
+}
+
+impl Format {
+    fn from_name(name: &str) -> Option<Self> {
+        let n = name.to_lowercase();
+        if ZIP_EXTENSIONS.iter().any(|it| n.ends_with(it)) {
+            Some(Format::Zip)
+        } else if Compression::from_name(&n).is_some() {
+            Some(Format::Tar)
+        } else {
+            None
+        }
+    }
+}
+
+fn file_name(path: &Path) -> String {
+    path.file_name()
+        .map_or("".to_string(), |it| it.to_string_lossy().to_string())
+}
+
+// whether the archive can be created with the name
+pub fn can_create(name: &str) -> bool {
+    let n = name.to_lowercase();
+    CREATE_EXTENSIONS.iter().any(|it| n.ends_with(it))
+}
+
+// whether the file can be extracted by its name
+pub fn can_extract(name: &str) -> bool {
+    Format::from_name(name).is_some()
+}
+
+// the name of the archive without the extension, `foo` for `foo.tar.gz`
+pub fn stem(name: &str) -> &str {
+    let n = name.to_lowercase();
+    [
+        ".tar.gz", ".tar.xz", ".tar.zst", ".tgz", ".txz", ".tzst", ".tar",
+    ]
+    .iter()
+    .chain(ZIP_EXTENSIONS.iter())
+    .find(|it| n.ends_with(*it))
+    .map_or(name, |it| &name[..name.len() - it.len()])
+}
+
+// the first of `foo (1).tar.gz`, `foo (2).tar.gz`... which does not exist
+pub fn unique_path(path: &Path) -> PathBuf {
+    let name = file_name(path);
+    let (stem, ext) = name.split_at(stem(&name).len());
+    let parent = path.parent().unwrap_or_else(|| Path::new("/"));
+    (1..)
+        .map(|i| parent.join(format!("{} ({}){}", stem, i, ext)))
+        .find(|it| it.symlink_metadata().is_err())
+        .unwrap()
+}
+
+// the files to pack with their names in the archive, the dirs are walked without following
+// the symbolic links
+fn walk(paths: &[PathBuf]) -> Res<Vec<(PathBuf, String, Metadata)>> {
+    let mut re = Vec::new();
+    let mut stack: Vec<_> = paths
+        .iter()
+        .rev()
+        .map(|it| (it.clone(), file_name(it)))
+        .collect();
+    while let Some((path, name)) = stack.pop() {
+        let meta = path.symlink_metadata()?;
+        if meta.is_dir() {
+            let mut children: Vec<_> = fs::read_dir(&path)?
+                .filter_map(|it| it.ok())
+                .map(|it| it.path())
+                .collect();
+            children.sort();
+            for c in children.into_iter().rev() {
+                let n = format!("{}/{}", name, file_name(&c));
+                stack.push((c, n));
+            }
+        }
+        re.push((path, name, meta));
+    }
+    Ok(re)
+}
+
+fn write_tar<W: Write>(w: W, entries: &[(PathBuf, String, Metadata)], p: &Progress) -> Res<W> {
+    let mut b = Builder::new(w);
+    b.follow_symlinks(false);
+    for (path, name, meta) in entries {
+        p.check()?;
+        b.append_path_with_name(path, name)?;
+        p.advance(file_bytes(meta), 1);
+    }
+    Ok(b.into_inner()?)
+}
+
+fn zip_time(meta: &Metadata) -> zip::DateTime {
+    meta.modified()
+        .ok()
+        .and_then(|it| {
+            let t: DateTime<Local> = it.into();
+            zip::DateTime::from_date_and_time(
+                t.year() as u16,
+                t.month() as u8,
+                t.day() as u8,
+                t.hour() as u8,
+                t.minute() as u8,
+                t.second() as u8,
+            )
+            .ok()
+        })
+        .unwrap_or_default()
+}
+
+fn write_zip(to: File, entries: &[(PathBuf, String, Metadata)], p: &Progress) -> Void {
+    let mut z = ZipWriter::new(to);
+    for (path, name, meta) in entries {
+        p.check()?;
+        let opts = FileOptions::default()
+            .compression_method(CompressionMethod::Deflated)
+            .last_modified_time(zip_time(meta))
+            .unix_permissions(meta.permissions().mode() & 0o7777);
+        let ft = meta.file_type();
+        if ft.is_dir() {
+            z.add_directory(format!("{}/", name), opts)?;
+        } else if ft.is_file() {
+            z.start_file(name.as_str(), opts.large_file(meta.len() > u32::MAX as u64))?;
+            io::copy(&mut File::open(path)?, &mut z)?;
+        } else {
+            log::debug!("skip {}, it can not be zipped", path.display());
+        }
+        p.advance(file_bytes(meta), 1);
+    }
+    z.finish()?;
+    Ok(())
+}
+
+fn file_bytes(meta: &Metadata) -> u64 {
+    if meta.is_file() {
+        meta.len()
+    } else {
+        0
+    }
+}
+
+// pack the files into the archive `to`, which must not exist, it is removed if the packing fails
+// or is cancelled
+pub fn pack(paths: &[PathBuf], to: &Path, p: &Progress) -> Void {
+    let entries = walk(paths)?;
+    p.add_total(
+        entries.iter().map(|(_, _, m)| file_bytes(m)).sum(),
+        entries.len() as u64,
+    );
+
+    let f = OpenOptions::new().write(true).create_new(true).open(to)?;
+    let re = match Compression::from_name(&file_name(to)) {
+        Some(Compression::Gz) => write_tar(GzEncoder::new(f, Default::default()), &entries, p)
+            .and_then(|it| Ok(it.finish().map(|_| ())?)),
+        Some(Compression::Zst) => zstd::stream::write::Encoder::new(f, ZSTD_LEVEL)
+            .map_err(Error::from)
+            .and_then(|it| write_tar(it, &entries, p))
+            .and_then(|it| Ok(it.finish().map(|_| ())?)),
+        _ => write_zip(f, &entries, p),
+    };
+    if re.is_err() {
+        let _ = fs::remove_file(to);
+    }
+    re
+}
+
+// the reader advancing the progress by the bytes read from the archive
+struct Counted<R> {
+    inner: R,
+    p: Arc<Progress>,
+}
+
+impl<R: Read> Read for Counted<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let n = self.inner.read(buf)?;
+        self.p.advance(n as u64, 0);
+        Ok(n)
+    }
+}
+
+fn unpack_tar(archive: &Path, dir: &Path, p: Arc<Progress>) -> Void {
+    let f = File::open(archive)?;
+    p.add_total(f.metadata()?.len(), 0);
+    let counted = Counted {
+        inner: f,
+        p: p.clone(),
+    };
+    let mut a = Compression::reader(&file_name(archive), counted)?;
+    a.set_preserve_permissions(true);
+    for e in a.entries()? {
+        p.check()?;
+        e?.unpack_in(dir)?;
+    }
+    Ok(())
+}
+
+// create the missing dirs of `name` in `dir`, the last component too if `with_last`, no symbolic
+// link is followed, so an entry can not be written out of `dir` through a link extracted before,
+// false if a component is a link or a file
+fn make_parents(dir: &Path, name: &Path, with_last: bool) -> Res<bool> {
+    let components: Vec<_> = name.components().collect();
+    let count = if with_last {
+        components.len()
+    } else {
+        components.len().saturating_sub(1)
+    };
+    let mut p = dir.to_path_buf();
+    for c in &components[..count] {
+        p.push(c);
+        match p.symlink_metadata() {
+            Ok(m) if m.is_dir() => {}
+            Ok(_) => return Ok(false),
+            Err(_) => fs::create_dir(&p)?,
+        }
+    }
+    Ok(true)
+}
+
+fn unpack_zip(archive: &Path, dir: &Path, p: &Progress) -> Void {
+    let mut za = ZipArchive::new(File::open(archive)?)?;
+    p.add_total(
+        (0..za.len())
+            .filter_map(|i| za.by_index(i).ok().map(|it| it.size()))
+            .sum(),
+        za.len() as u64,
+    );
+    for i in 0..za.len() {
+        p.check()?;
+        let mut f = za.by_index(i)?;
+        let name = match f.enclosed_name() {
+            Some(n) => n.to_path_buf(),
+            None => {
+                log::debug!("skip {}, it is not in the archive dir", f.name());
+                continue;
+            }
+        };
+        if !make_parents(dir, &name, f.is_dir())? {
+            log::debug!("skip {}, it is not in the archive dir", f.name());
+            continue;
+        }
+        let to = dir.join(&name);
+        if !f.is_dir() {
+            match f.unix_mode() {
+                Some(m) if m & S_IFMT == S_IFLNK => {
+                    let mut target = String::new();
+                    f.read_to_string(&mut target)?;
+                    std::os::unix::fs::symlink(target, &to)?;
+                }
+                m => {
+                    let mut out = OpenOptions::new()
+                        .write(true)
+                        .create_new(true)
+                        .custom_flags(libc::O_NOFOLLOW)
+                        .open(&to)?;
+                    io::copy(&mut f, &mut out)?;
+                    if let Some(m) = m {
+                        out.set_permissions(Permissions::from_mode(m & 0o7777))?;
+                    }
+                }
+            }
+        }
+        p.advance(f.size(), 1);
+    }
+    Ok(())
+}
+
+// the names of the top level entries of the archive, the entries out of the archive dir are
+// left out as they are not extracted
+fn roots(archive: &Path) -> Res<BTreeSet<String>> {
+    let first = |name: &Path| {
+        name.components().find_map(|it| match it {
+            Component::Normal(n) => Some(n.to_string_lossy().to_string()),
+            _ => None,
+        })
+    };
+    let mut re = BTreeSet::new();
+    match Format::from_name(&file_name(archive)) {
+        Some(Format::Zip) => {
+            let mut za = ZipArchive::new(File::open(archive)?)?;
+            for i in 0..za.len() {
+                re.extend(za.by_index(i)?.enclosed_name().and_then(first));
+            }
+        }
+        Some(Format::Tar) => {
+            let mut a = Compression::reader(&file_name(archive), File::open(archive)?)?;
+            for e in a.entries()? {
+                re.extend(first(&e?.path()?));
+            }
+        }
+        None => return Err(Error::InvalidArchive(archive.display().to_string())),
+    }
+    Ok(re)
+}
+
+// where the archive is extracted, next to it into the dir named after it, or as the only root
+// entry of the archive if it has one, so there is no `foo/foo`
+pub fn target(archive: &Path) -> Res<PathBuf> {
+    let name = file_name(archive);
+    let parent = archive.parent().unwrap_or_else(|| Path::new("/"));
+    let roots = roots(archive)?;
+    Ok(match roots.iter().collect::<Vec<_>>().as_slice() {
+        [root] => parent.join(root),
+        _ => parent.join(stem(&name)),
+    })
+}
+
+// the most recently modified of the files to pack, to compare with an existing archive
+pub fn newest(paths: &[PathBuf]) -> Res<PathBuf> {
+    let entries = walk(paths)?;
+    entries
+        .into_iter()
+        .max_by_key(|(_, _, m)| m.modified().ok())
+        .map(|(p, _, _)| p)
+        .ok_or_else(|| Error::PathNotExists("".to_string()))
+}
+
+// extract the archive to `to`, usually its `target`, which must not exist
+pub fn unpack(archive: &Path, to: &Path, p: Arc<Progress>) -> Void {
+    let name = file_name(archive);
+    let parent = archive.parent().unwrap_or_else(|| Path::new("/"));
+    let tmp = parent.join(format!(".{}.extracting", stem(&name)));
+    let tmp = if tmp.symlink_metadata().is_ok() {
+        unique_path(&tmp)
+    } else {
+        tmp
+    };
+    fs::create_dir(&tmp)?;
+
+    let re = match Format::from_name(&name) {
+        Some(Format::Zip) => unpack_zip(archive, &tmp, &p),
+        Some(Format::Tar) => unpack_tar(archive, &tmp, p.clone()),
+        None => Err(Error::InvalidArchive(archive.display().to_string())),
+    };
+    if let Err(e) = re {
+        let _ = fs::remove_dir_all(&tmp);
+        return Err(e);
+    }
+
+    let roots: Vec<_> = fs::read_dir(&tmp)?
+        .filter_map(|it| it.ok())
+        .map(|it| it.path())
+        .collect();
+    let from = match roots.as_slice() {
+        [root] => root.clone(),
+        _ => tmp.clone(),
+    };
+    if to.symlink_metadata().is_ok() {
+        let _ = fs::remove_dir_all(&tmp);
+        return Err(Error::FileAlreadyExists(to.display().to_string()));
+    }
+    fs::rename(&from, to)?;
+    if from != tmp {
+        fs::remove_dir(&tmp)?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("fff-pack-{}-{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    // a zip of the `(name, content)` entries, the entries named `link` are symbolic links to
+    // their content, the zip writer can not add links so their modes are patched after
+    fn zip_with_link(path: &Path, entries: &[(&str, &str)]) {
+        let mut z = ZipWriter::new(File::create(path).unwrap());
+        for (name, content) in entries {
+            z.start_file(*name, FileOptions::default()).unwrap();
+            z.write_all(content.as_bytes()).unwrap();
+        }
+        z.finish().unwrap();
+
+        let mut bs = fs::read(path).unwrap();
+        let mut i = 0;
+        while i + 46 < bs.len() {
+            if bs[i..i + 4] == [0x50, 0x4b, 0x01, 0x02] {
+                let len = u16::from_le_bytes([bs[i + 28], bs[i + 29]]) as usize;
+                if &bs[i + 46..i + 46 + len] == b"link" {
+                    let attr = (S_IFLNK | 0o777) << 16;
+                    bs[i + 38..i + 42].copy_from_slice(&attr.to_le_bytes());
+                }
+            }
+            i += 1;
+        }
+        fs::write(path, bs).unwrap();
+    }
+
+    #[test]
+    fn zip_links_are_not_followed() {
+        let dir = dir("slip");
+        let outside = dir.join("outside");
+        fs::create_dir(&outside).unwrap();
+        let archive = dir.join("a.zip");
+        let target = outside.display().to_string();
+        zip_with_link(
+            &archive,
+            &[("link", &target), ("link/passwd", "x"), ("b", "b")],
+        );
+
+        let to = dir.join("to");
+        fs::create_dir(&to).unwrap();
+        unpack_zip(&archive, &to, &Progress::new()).unwrap();
+        assert!(to
+            .join("link")
+            .symlink_metadata()
+            .unwrap()
+            .file_type()
+            .is_symlink());
+        assert!(!outside.join("passwd").exists());
+        assert_eq!(fs::read_to_string(to.join("b")).unwrap(), "b");
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn extract_to_the_target() {
+        let dir = dir("target");
+        fs::create_dir_all(dir.join("d/e")).unwrap();
+        fs::write(dir.join("d/e/f"), "f").unwrap();
+        fs::write(dir.join("g"), "g").unwrap();
+
+        let single = dir.join("single.tar.gz");
+        pack(&[dir.join("d")], &single, &Progress::new()).unwrap();
+        assert_eq!(target(&single).unwrap(), dir.join("d"));
+        assert!(pack(&[dir.join("g")], &single, &Progress::new()).is_err());
+
+        let multiple = dir.join("multiple.zip");
+        pack(&[dir.join("d"), dir.join("g")], &multiple, &Progress::new()).unwrap();
+        assert_eq!(target(&multiple).unwrap(), dir.join("multiple"));
+
+        let to = dir.join("d (1)");
+        unpack(&single, &to, Arc::new(Progress::new())).unwrap();
+        assert_eq!(fs::read_to_string(to.join("e/f")).unwrap(), "f");
+        assert!(unpack(&multiple, &to, Arc::new(Progress::new())).is_err());
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,279 @@
+use crate::model::file::archive::{create, Archive, Cache, Entry, Index};
+use crate::model::file::protocol::Protocol;
+use crate::model::file::{InnerFile, LinkInfo, ProtocolInfo};
+use crate::model::result::{Res, Void};
+use flate2::read::GzDecoder;
+use std::fs::File;
+use std::io::Read;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::{Duration, UNIX_EPOCH};
+use tar::EntryType;
+use xz2::read::XzDecoder;
+use zstd::stream::read::Decoder as ZstdDecoder;
+
+const GZ_EXTENSIONS: [&str; 2] = [".tar.gz", ".tgz"];
+const XZ_EXTENSIONS: [&str; 2] = [".tar.xz", ".txz"];
+const ZST_EXTENSIONS: [&str; 2] = [".tar.zst", ".tzst"];
+
+pub struct TarProtocol(Cache);
+
+impl TarProtocol {
+    pub fn new() -> Self {
+        TarProtocol(Cache::new())
+    }
+}
+
+impl Protocol for TarProtocol {
+    fn name(&self) -> &'static str {
+        "tar"
+    }
+
+    fn support(&self, file: &InnerFile) -> bool {
+        file.is_file() && Compression::from_name(&file.info().name).is_some()
+    }
+
+    fn create(&self, info: ProtocolInfo, path: &Path) -> Res<InnerFile> {
+        let archive = self.0.get(&info, |p| Ok(Arc::new(Tar::load(p)?)))?;
+        create(info, path, archive)
+    }
+}
+
+pub(super) enum Compression {
+    None,
+    Gz,
+    Xz,
+    Zst,
+}
+
+impl Compression {
+    pub(super) fn from_name(name: &str) -> Option<Self> {
+        let n = name.to_lowercase();
+        if n.ends_with(".tar") {
+            Some(Compression::None)
+        } else if GZ_EXTENSIONS.iter().any(|it| n.ends_with(it)) {
+            Some(Compression::Gz)
+        } else if XZ_EXTENSIONS.iter().any(|it| n.ends_with(it)) {
+            Some(Compression::Xz)
+        } else if ZST_EXTENSIONS.iter().any(|it| n.ends_with(it)) {
+            Some(Compression::Zst)
+        } else {
+            None
+        }
+    }
+
+    // the tar read from `r`, which is the content of the archive file named `name`
+    pub(super) fn reader<R: Read + 'static>(name: &str, r: R) -> Res<tar::Archive<Box<dyn Read>>> {
+        let r: Box<dyn Read> = match Compression::from_name(name) {
+            Some(Compression::Gz) => Box::new(GzDecoder::new(r)),
+            Some(Compression::Xz) => Box::new(XzDecoder::new(r)),
+            Some(Compression::Zst) => Box::new(ZstdDecoder::new(r)?),
+            _ => Box::new(r),
+        };
+        Ok(tar::Archive::new(r))
+    }
+
+    fn open(path: &Path) -> Res<tar::Archive<Box<dyn Read>>> {
+        let name = path
+            .file_name()
+            .map_or("".into(), |it| it.to_string_lossy());
+        Compression::reader(&name, File::open(path)?)
+    }
+}
+
+struct Tar {
+    path: PathBuf,
+    index: Index,
+}
+
+fn type_bits(tp: EntryType) -> u32 {
+    match tp {
+        EntryType::Directory => 0o040000,
+        EntryType::Symlink => 0o120000,
+        EntryType::Char => 0o020000,
+        EntryType::Block => 0o060000,
+        EntryType::Fifo => 0o010000,
+        _ => 0o100000,
+    }
+}
+
+impl Tar {
+    fn load(path: &Path) -> Res<Self> {
+        let mut archive = Compression::open(path)?;
+        let mut index = Index::new();
+        // (entry path, link target in archive or the raw one out of it, is hard link)
+        let mut links: Vec<(PathBuf, Result<PathBuf, String>, bool)> = Vec::new();
+
+        for e in archive.entries()? {
+            let e = e?;
+            let h = e.header();
+            let raw = e.path()?.display().to_string();
+            let tp = h.entry_type();
+            if tp.is_pax_global_extensions()
+                || tp.is_pax_local_extensions()
+                || tp.is_gnu_longname()
+                || tp.is_gnu_longlink()
+            {
+                continue;
+            }
+
+            let p = Index::normalize(&raw);
+            if let Some(ln) = e.link_name()? {
+                let ln = ln.display().to_string();
+                // an absolute symbolic link points out of the archive
+                let target = if tp.is_hard_link() {
+                    Ok(Index::normalize(&ln))
+                } else if ln.starts_with('/') {
+                    Err(ln)
+                } else {
+                    let parent = p.parent().unwrap_or_else(|| Path::new("/"));
+                    Ok(Index::normalize(&parent.join(ln).display().to_string()))
+                };
+                links.push((p, target, tp.is_hard_link()));
+            }
+
+            index.insert(
+                &raw,
+                Entry {
+                    raw: raw.clone(),
+                    size: h.size()?,
+                    mode: type_bits(tp) | (h.mode()? & 0o7777),
+                    modified: h
+                        .mtime()
+                        .ok()
+                        .map(|it| UNIX_EPOCH + Duration::from_secs(it)),
+                    is_dir: tp.is_dir(),
+                    link: None,
+                },
+            );
+        }
+
+        links.into_iter().for_each(|(p, target, hard)| {
+            let size = target
+                .as_ref()
+                .ok()
+                .and_then(|it| index.get(it))
+                .map(|it| it.size);
+            if let Some(e) = index.get_mut(&p) {
+                if hard {
+                    e.size = size.unwrap_or(e.size);
+                }
+                e.link = Some(LinkInfo {
+                    broken: size.is_none(),
+                    target: target.map_or_else(|it| it, |it| it.display().to_string()),
+                });
+            }
+        });
+
+        Ok(Tar {
+            path: path.to_path_buf(),
+            index,
+        })
+    }
+}
+
+impl Archive for Tar {
+    fn index(&self) -> &Index {
+        &self.index
+    }
+
+    fn extract(&self, path: &Path, to: &Path) -> Void {
+        // hard links and symbolic links are followed to the entry holding the content
+        let (p, _) = self.index.content(path)?;
+
+        let mut archive = Compression::open(&self.path)?;
+        for e in archive.entries()? {
+            let mut e = e?;
+            if Index::normalize(&e.path()?.display().to_string()) == p {
+                std::io::copy(&mut e, &mut File::create(to)?)?;
+                break;
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::context::Context;
+    use crate::model::file::path::InnerPath;
+    use crate::model::file::protocol;
+    use crate::model::result::Error;
+    use std::convert::TryFrom;
+    use std::fs;
+    use std::os::unix::fs::symlink;
+
+    // a tar of d/f, l -> d/f, ld -> d, lb -> none and la -> /etc/hostname
+    fn tar_with_links(dir: &Path) -> PathBuf {
+        let src = dir.join("src");
+        fs::create_dir_all(src.join("d")).unwrap();
+        fs::write(src.join("d/f"), "f").unwrap();
+        symlink("d/f", src.join("l")).unwrap();
+        symlink("d", src.join("ld")).unwrap();
+        symlink("none", src.join("lb")).unwrap();
+        symlink("/etc/hostname", src.join("la")).unwrap();
+
+        let t = dir.join("links.tar");
+        let mut b = tar::Builder::new(File::create(&t).unwrap());
+        b.follow_symlinks(false);
+        b.append_dir_all("", &src).unwrap();
+        b.finish().unwrap();
+        t
+    }
+
+    #[test]
+    fn extract_links() {
+        let dir = std::env::temp_dir().join(format!("fff-tar-links-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        let t = Tar::load(&tar_with_links(&dir)).unwrap();
+
+        let to = dir.join("out");
+        t.extract(Path::new("/l"), &to).unwrap();
+        assert_eq!(fs::read_to_string(&to).unwrap(), "f");
+        t.extract(Path::new("/ld/f"), &to).unwrap();
+
+        let err = |p: &str| t.extract(Path::new(p), &dir.join("none")).unwrap_err();
+        assert!(matches!(err("/ld"), Error::SpecialFile(_)));
+        assert!(matches!(err("/lb"), Error::PathNotExists(_)));
+        assert!(matches!(err("/la"), Error::PathNotExists(_)));
+        assert!(!dir.join("none").exists());
+
+        let la = t
+            .index
+            .get(Path::new("/la"))
+            .unwrap()
+            .link
+            .as_ref()
+            .unwrap();
+        assert!(la.broken);
+        assert_eq!(la.target, "/etc/hostname");
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[tokio::test]
+    async fn browse_links() {
+        let dir = std::env::temp_dir().join(format!("fff-tar-browse-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        let t = tar_with_links(&dir);
+        protocol::register(TarProtocol::new());
+        let ctx = Context::for_test(&dir);
+
+        let get = |p: &str| InnerFile::try_from(InnerPath::new_protocol(&t, "tar", Path::new(p)));
+        let l = get("/l").unwrap();
+        assert!(l.is_file());
+        assert_eq!(l.info().link.as_ref().unwrap().target, "/d/f");
+
+        let ld = get("/ld").unwrap();
+        assert!(ld.is_dir());
+        assert!(ld.info().link.is_some());
+        let fs: Vec<_> = match ld {
+            InnerFile::Dir(d) => d.list(&ctx).await.unwrap(),
+            InnerFile::File(_) => unreachable!(),
+        };
+        assert_eq!(fs.len(), 1);
+        assert_eq!(fs[0].info().path, Path::new("/ld/f"));
+        assert!(get("/lb").unwrap().is_file());
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,96 @@
+use crate::model::file::archive::{
+    create, Archive, Cache, Entry, Index, DEFAULT_DIR_MODE, DEFAULT_FILE_MODE,
+};
+use crate::model::file::protocol::Protocol;
+use crate::model::file::{InnerFile, ProtocolInfo};
+use crate::model::result::{Res, Void};
+use chrono::{Local, NaiveDate, TimeZone};
+use std::fs::File;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::SystemTime;
+use zip::ZipArchive;
+
+const EXTENSIONS: [&str; 5] = [".zip", ".jar", ".war", ".apk", ".whl"];
+
+pub struct ZipProtocol(Cache);
+
+impl ZipProtocol {
+    pub fn new() -> Self {
+        ZipProtocol(Cache::new())
+    }
+}
+
+impl Protocol for ZipProtocol {
+    fn name(&self) -> &'static str {
+        "zip"
+    }
+
+    fn support(&self, file: &InnerFile) -> bool {
+        let name = file.info().name.to_lowercase();
+        file.is_file() && EXTENSIONS.iter().any(|it| name.ends_with(it))
+    }
+
+    fn create(&self, info: ProtocolInfo, path: &Path) -> Res<InnerFile> {
+        let archive = self.0.get(&info, |p| Ok(Arc::new(Zip::load(p)?)))?;
+        create(info, path, archive)
+    }
+}
+
+struct Zip {
+    path: PathBuf,
+    index: Index,
+}
+
+impl Zip {
+    fn load(path: &Path) -> Res<Self> {
+        let mut za = ZipArchive::new(File::open(path)?)?;
+        let mut index = Index::new();
+        for i in 0..za.len() {
+            let f = za.by_index(i)?;
+            let is_dir = f.is_dir();
+            let default_mode = if is_dir {
+                DEFAULT_DIR_MODE
+            } else {
+                DEFAULT_FILE_MODE
+            };
+
+            index.insert(
+                f.name(),
+                Entry {
+                    raw: f.name().to_string(),
+                    size: f.size(),
+                    mode: f.unix_mode().unwrap_or(default_mode),
+                    modified: to_system_time(f.last_modified()),
+                    is_dir,
+                    link: None,
+                },
+            );
+        }
+
+        Ok(Zip {
+            path: path.to_path_buf(),
+            index,
+        })
+    }
+}
+
+fn to_system_time(dt: zip::DateTime) -> Option<SystemTime> {
+    let n = NaiveDate::from_ymd_opt(dt.year() as i32, dt.month() as u32, dt.day() as u32)?
+        .and_hms_opt(dt.hour() as u32, dt.minute() as u32, dt.second() as u32)?;
+    Local.from_local_datetime(&n).single().map(|it| it.into())
+}
+
+impl Archive for Zip {
+    fn index(&self) -> &Index {
+        &self.index
+    }
+
+    fn extract(&self, path: &Path, to: &Path) -> Void {
+        let raw = self.index.content(path)?.1.raw.clone();
+        let mut za = ZipArchive::new(File::open(&self.path)?)?;
+        let mut f = za.by_name(&raw)?;
+        std::io::copy(&mut f, &mut File::create(to)?)?;
+        Ok(())
+    }
+}
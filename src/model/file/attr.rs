@@ -0,0 +1,413 @@
+// Change the attributes of local files: the mode by an octal or a symbolic expression like
+// `u+x,go-w`, where `^` toggles the bits, the owner and the group, and the access and modified
+// times.
+use crate::model::file::conflict::error_desc;
+use crate::model::file::file_mode::mode_string;
+use crate::model::file::stat::{group_id, group_name, user_id, user_name};
+use crate::model::file::FileInfo;
+use crate::model::result::{Error, Res, Void};
+use crate::model::task::Progress;
+use chrono::{DateTime, Local, NaiveDate, NaiveDateTime, TimeZone};
+use filetime::FileTime;
+use std::fs::{self, Permissions};
+use std::os::unix::fs::{lchown, PermissionsExt};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::SystemTime;
+
+const TIME_FORMAT: &str = "%Y-%m-%d %H:%M:%S";
+const OPERATORS: &str = "+-=^";
+
+// the classes of a symbolic mode, with the shift of their rwx bits and their special bit
+const CLASSES: [(u32, u32); 3] = [(6, 0o4000), (3, 0o2000), (0, 0o1000)];
+const OTHERS: usize = 2;
+
+pub struct Clause {
+    classes: Vec<usize>,
+    op: char,
+    perms: String,
+}
+
+pub enum ModeExpr {
+    Octal(u32),
+    Symbolic(Vec<Clause>),
+}
+
+fn invalid(s: &str, reason: &str) -> Error {
+    Error::InvalidAttribute(format!("{}: {}", s, reason))
+}
+
+fn parse_clauses(part: &str, clauses: &mut Vec<Clause>) -> Void {
+    let mut chars = part.chars().peekable();
+    let mut classes = Vec::new();
+    while let Some(c) = chars.peek() {
+        match c {
+            'u' => classes.push(0),
+            'g' => classes.push(1),
+            'o' => classes.push(OTHERS),
+            'a' => classes.extend(0..3),
+            _ => break,
+        }
+        chars.next();
+    }
+    if classes.is_empty() {
+        classes.extend(0..3);
+    }
+
+    let count = clauses.len();
+    while let Some(op) = chars.next() {
+        if !OPERATORS.contains(op) {
+            return Err(invalid(part, "unknown operator"));
+        }
+        let mut perms = String::new();
+        while let Some(&c) = chars.peek() {
+            if OPERATORS.contains(c) {
+                break;
+            }
+            if !"rwxXst".contains(c) {
+                return Err(invalid(part, "unknown permission"));
+            }
+            perms.push(c);
+            chars.next();
+        }
+        clauses.push(Clause {
+            classes: classes.clone(),
+            op,
+            perms,
+        });
+    }
+    if clauses.len() == count {
+        return Err(invalid(part, "no operator"));
+    }
+    Ok(())
+}
+
+impl ModeExpr {
+    pub fn parse(s: &str) -> Res<Self> {
+        let s = s.trim();
+        if !s.is_empty() && s.chars().all(|it| it.is_digit(8)) {
+            return match u32::from_str_radix(s, 8) {
+                Ok(m) if m <= 0o7777 => Ok(ModeExpr::Octal(m)),
+                _ => Err(invalid(s, "out of range")),
+            };
+        }
+
+        let mut clauses = Vec::new();
+        for part in s.split(',') {
+            parse_clauses(part, &mut clauses)?;
+        }
+        Ok(ModeExpr::Symbolic(clauses))
+    }
+
+    // the new mode of a file with `mode`, `X` is executable only for dirs and executables
+    pub fn apply(&self, mode: u32, is_dir: bool) -> u32 {
+        let clauses = match self {
+            ModeExpr::Octal(m) => return (mode & !0o7777) | m,
+            ModeExpr::Symbolic(c) => c,
+        };
+
+        let mut mode = mode;
+        for c in clauses {
+            let executable = is_dir || mode & 0o111 != 0;
+            let mut bits = 0;
+            let mut mask = 0;
+            for &class in c.classes.iter() {
+                let (shift, special) = CLASSES[class];
+                mask |= (0o7 << shift) | special;
+                for p in c.perms.chars() {
+                    bits |= match p {
+                        'r' => 0o4 << shift,
+                        'w' => 0o2 << shift,
+                        'x' => 0o1 << shift,
+                        'X' if executable => 0o1 << shift,
+                        's' if class != OTHERS => special,
+                        't' if class == OTHERS => special,
+                        _ => 0,
+                    };
+                }
+            }
+            mode = match c.op {
+                '+' => mode | bits,
+                '-' => mode & !bits,
+                '^' => mode ^ bits,
+                _ => (mode & !mask) | bits,
+            };
+        }
+        mode
+    }
+}
+
+// the attribute to change
+#[derive(Clone, Copy)]
+pub enum Attribute {
+    Mode,
+    Owner,
+    Times,
+}
+
+impl Attribute {
+    pub fn prompt(self) -> &'static str {
+        match self {
+            Attribute::Mode => "Mode (octal, or like u+x,go-w)",
+            Attribute::Owner => "Owner (user:group)",
+            Attribute::Times => "Time (now or YYYY-MM-DD HH:MM:SS, after a or m for one of them)",
+        }
+    }
+
+    pub fn parse(self, s: &str) -> Res<Change> {
+        match self {
+            Attribute::Mode => Ok(Change::Mode(ModeExpr::parse(s)?)),
+            Attribute::Owner => Change::parse_owner(s),
+            Attribute::Times => Change::parse_times(s),
+        }
+    }
+
+    // the lines to preview the change typed so far for the files, None if nothing is typed
+    pub fn preview(self, text: &str, files: &[FileInfo]) -> Option<Vec<String>> {
+        match self.parse(text) {
+            _ if text.is_empty() => None,
+            Ok(c) => Some(files.iter().map(|it| c.preview(it)).collect()),
+            Err(e) => Some(vec![error_desc(&e)]),
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Attribute::Mode => "mode",
+            Attribute::Owner => "owner",
+            Attribute::Times => "times",
+        }
+    }
+}
+
+pub enum Change {
+    Mode(ModeExpr),
+    // the uid and the gid, None keeps it
+    Owner(Option<u32>, Option<u32>),
+    // the access and the modified times, None keeps it
+    Times(Option<SystemTime>, Option<SystemTime>),
+}
+
+fn parse_time(s: &str) -> Res<SystemTime> {
+    if s == "now" {
+        return Ok(SystemTime::now());
+    }
+    let naive = NaiveDateTime::parse_from_str(s, TIME_FORMAT)
+        .or_else(|_| NaiveDateTime::parse_from_str(s, "%Y-%m-%d %H:%M"))
+        .or_else(|_| NaiveDate::parse_from_str(s, "%Y-%m-%d").map(|it| it.and_hms(0, 0, 0)))
+        .map_err(|_| invalid(s, "invalid time"))?;
+    match Local.from_local_datetime(&naive).earliest() {
+        Some(t) => Ok(t.into()),
+        None => Err(invalid(s, "invalid local time")),
+    }
+}
+
+fn time_str(t: Option<SystemTime>) -> String {
+    t.map_or("-".to_string(), |it| {
+        let dt: DateTime<Local> = it.into();
+        dt.format(TIME_FORMAT).to_string()
+    })
+}
+
+impl Change {
+    // `user:group`, either of them can be omitted
+    pub fn parse_owner(s: &str) -> Res<Self> {
+        let s = s.trim();
+        let (user, group) = match s.find(':') {
+            Some(i) => (&s[..i], &s[i + 1..]),
+            None => (s, ""),
+        };
+        let uid = match user {
+            "" => None,
+            u => Some(user_id(u).ok_or_else(|| invalid(u, "no such user"))?),
+        };
+        let gid = match group {
+            "" => None,
+            g => Some(group_id(g).ok_or_else(|| invalid(g, "no such group"))?),
+        };
+        if uid.is_none() && gid.is_none() {
+            return Err(invalid(s, "no user or group"));
+        }
+        Ok(Change::Owner(uid, gid))
+    }
+
+    // `now` or `YYYY-MM-DD[ HH:MM[:SS]]` for both times, prefixed by `a ` or `m ` for one of them
+    pub fn parse_times(s: &str) -> Res<Self> {
+        let s = s.trim();
+        let (accessed, modified, time) = match s.get(..2) {
+            Some("a ") => (true, false, &s[2..]),
+            Some("m ") => (false, true, &s[2..]),
+            _ => (true, true, s),
+        };
+        let t = parse_time(time.trim())?;
+        Ok(Change::Times(
+            Some(t).filter(|_| accessed),
+            Some(t).filter(|_| modified),
+        ))
+    }
+
+    // change the file, the mode of a symbolic link is not changed, as it has none
+    pub fn apply(&self, path: &Path) -> Void {
+        let meta = path.symlink_metadata()?;
+        match self {
+            Change::Mode(expr) => {
+                if meta.file_type().is_symlink() {
+                    return Ok(());
+                }
+                let mode = expr.apply(meta.permissions().mode(), meta.is_dir());
+                fs::set_permissions(path, Permissions::from_mode(mode & 0o7777))?;
+            }
+            Change::Owner(uid, gid) => lchown(path, *uid, *gid)?,
+            Change::Times(accessed, modified) => {
+                let a = accessed.map_or(FileTime::from_last_access_time(&meta), |it| {
+                    FileTime::from_system_time(it)
+                });
+                let m = modified.map_or(FileTime::from_last_modification_time(&meta), |it| {
+                    FileTime::from_system_time(it)
+                });
+                filetime::set_symlink_file_times(path, a, m)?;
+            }
+        }
+        Ok(())
+    }
+
+    // the line to preview the change of the file
+    pub fn preview(&self, info: &FileInfo) -> String {
+        let stat = info.stat.clone().unwrap_or_default();
+        let change = match self {
+            Change::Mode(expr) => format!(
+                "{} -> {}",
+                mode_string(info.mode),
+                mode_string(expr.apply(info.mode, info.is_dir))
+            ),
+            Change::Owner(uid, gid) => format!(
+                "{}:{} -> {}:{}",
+                stat.owner,
+                stat.group,
+                uid.map_or(stat.owner.clone(), user_name),
+                gid.map_or(stat.group.clone(), group_name)
+            ),
+            Change::Times(accessed, modified) => {
+                let mut re = Vec::new();
+                if modified.is_some() {
+                    re.push(format!(
+                        "modified {} -> {}",
+                        time_str(info.modified),
+                        time_str(*modified)
+                    ));
+                }
+                if accessed.is_some() {
+                    re.push(format!(
+                        "accessed {} -> {}",
+                        time_str(stat.accessed),
+                        time_str(*accessed)
+                    ));
+                }
+                re.join(", ")
+            }
+        };
+        format!("{}  {}", info.name, change)
+    }
+}
+
+// change the files, and the files in the dirs if `recursive`, the failed ones are returned
+pub fn apply_all(
+    paths: &[PathBuf],
+    change: &Change,
+    recursive: bool,
+    p: &Progress,
+) -> Res<Vec<(PathBuf, Error)>> {
+    let mut failed = Vec::new();
+    let mut stack: Vec<_> = paths.iter().rev().cloned().collect();
+    p.add_total(0, stack.len() as u64);
+    while let Some(path) = stack.pop() {
+        p.check()?;
+        if let Err(e) = change.apply(&path) {
+            failed.push((path.clone(), e));
+        }
+        if recursive
+            && path
+                .symlink_metadata()
+                .map(|it| it.is_dir())
+                .unwrap_or(false)
+        {
+            match fs::read_dir(&path) {
+                Ok(rd) => {
+                    for entry in rd {
+                        match entry {
+                            Ok(e) => {
+                                stack.push(e.path());
+                                p.add_total(0, 1);
+                            }
+                            Err(e) => failed.push((path.clone(), e.into())),
+                        }
+                    }
+                }
+                Err(e) => failed.push((path.clone(), e.into())),
+            }
+        }
+        p.advance(0, 1);
+    }
+    Ok(failed)
+}
+
+// change the files in the blocking pool, the failed ones are logged and returned
+pub async fn change_all(
+    paths: Vec<PathBuf>,
+    change: Change,
+    recursive: bool,
+    p: Arc<Progress>,
+) -> Res<Vec<(PathBuf, Error)>> {
+    let failed =
+        tokio::task::spawn_blocking(move || apply_all(&paths, &change, recursive, &p)).await??;
+    for (f, e) in failed.iter() {
+        log::error!("change {} failed: {:?}", f.display(), e);
+    }
+    Ok(failed)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn apply(expr: &str, mode: u32, is_dir: bool) -> u32 {
+        ModeExpr::parse(expr).unwrap().apply(mode, is_dir)
+    }
+
+    #[test]
+    fn octal_modes() {
+        assert_eq!(apply("755", 0o100644, false), 0o100755);
+        assert_eq!(apply(" 4750 ", 0o40777, true), 0o44750);
+        assert!(ModeExpr::parse("17777").is_err());
+    }
+
+    #[test]
+    fn symbolic_modes() {
+        assert_eq!(apply("u+x,go-w", 0o666, false), 0o744);
+        assert_eq!(apply("a=r", 0o4777, false), 0o444);
+        assert_eq!(apply("+x", 0o644, false), 0o755);
+        assert_eq!(apply("u=rw,g=,o=", 0o777, false), 0o600);
+        assert_eq!(apply("o^w", 0o664, false), 0o666);
+        assert_eq!(apply("u+s,g+s,o+t", 0o755, true), 0o7755);
+        assert_eq!(apply("o+s,u+t", 0o755, true), 0o755);
+        assert_eq!(apply("u+w-x", 0o555, false), 0o655);
+    }
+
+    #[test]
+    fn executable_bit() {
+        assert_eq!(apply("a+X", 0o644, false), 0o644);
+        assert_eq!(apply("a+X", 0o744, false), 0o755);
+        assert_eq!(apply("a+X", 0o644, true), 0o755);
+    }
+
+    #[test]
+    fn invalid_modes() {
+        for e in &["", "u", "u+y", "z+x", "u+x,", "9"] {
+            assert!(
+                matches!(ModeExpr::parse(e), Err(Error::InvalidAttribute(_))),
+                "{}",
+                e
+            );
+        }
+    }
+}
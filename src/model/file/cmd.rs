@@ -1,3 +1,94 @@
+// Run the custom commands of the config. The placeholders of a command line are expanded to
+// the quoted paths, and the line is run by `sh`:
+//
+//   %f  the selected file           %F  the marked files, or the selected one
+//   %d  the current dir             %D  the current dir of the group switched from
+//   %n  the name of the selected file
+//   %%  a literal %
+use crate::model::result::{Error, Void};
+use crate::model::task::Progress;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+use std::time::Duration;
 
+const WAIT_INTERVAL: Duration = Duration::from_millis(100);
 
-pub async fn execute(_str: &str) {}
+pub struct Placeholders {
+    pub selected: Option<PathBuf>,
+    pub marked: Vec<PathBuf>,
+    pub dir: PathBuf,
+    pub other_dir: PathBuf,
+}
+
+// quote `s` as a single word of `sh`
+fn quote(s: &str) -> String {
+    format!("'{}'", s.replace('\'', "'\\''"))
+}
+
+fn quote_path(path: &Path) -> String {
+    quote(&path.display().to_string())
+}
+
+// the line with the placeholders expanded, an unknown one is kept as it is
+pub fn expand(line: &str, ph: &Placeholders) -> String {
+    let mut re = String::new();
+    let mut chars = line.chars();
+    while let Some(c) = chars.next() {
+        if c != '%' {
+            re.push(c);
+            continue;
+        }
+        match chars.next() {
+            Some('f') => re.push_str(&ph.selected.as_deref().map_or("".to_string(), quote_path)),
+            Some('F') => {
+                let marked: Vec<_> = ph.marked.iter().map(|it| quote_path(it)).collect();
+                re.push_str(&marked.join(" "));
+            }
+            Some('d') => re.push_str(&quote_path(&ph.dir)),
+            Some('D') => re.push_str(&quote_path(&ph.other_dir)),
+            Some('n') => {
+                let name = ph.selected.as_ref().and_then(|it| it.file_name());
+                re.push_str(&quote(&name.map_or("".into(), |it| it.to_string_lossy())));
+            }
+            Some('%') => re.push('%'),
+            Some(c) => {
+                re.push('%');
+                re.push(c);
+            }
+            None => re.push('%'),
+        }
+    }
+    re
+}
+
+// the command to run the expanded line in `dir`
+pub fn command(line: &str, dir: &Path) -> Command {
+    let mut cmd = Command::new("sh");
+    cmd.arg("-c").arg(line).current_dir(dir);
+    cmd
+}
+
+// run the command without a terminal until it exits, it is killed if the task is cancelled
+pub fn wait(mut cmd: Command, p: &Progress) -> Void {
+    let mut child = cmd
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .spawn()?;
+    p.add_total(0, 1);
+    loop {
+        if let Some(status) = child.try_wait()? {
+            if !status.success() {
+                return Err(Error::CommandFailed(status.to_string()));
+            }
+            p.advance(0, 1);
+            return Ok(());
+        }
+        if let Err(e) = p.check() {
+            child.kill()?;
+            child.wait()?;
+            return Err(e);
+        }
+        std::thread::sleep(WAIT_INTERVAL);
+    }
+}
@@ -0,0 +1,134 @@
+// Resolve the name conflicts of paste, move, rename and restore, where the target exists.
+use crate::config::enums::ConflictPolicy;
+use crate::model::file::readable_size;
+use crate::model::result::Error;
+use chrono::{DateTime, Local};
+use std::fs::Metadata;
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+
+fn modified(meta: &Metadata) -> Option<SystemTime> {
+    meta.modified().ok()
+}
+
+// the target to write `from` to when `to` exists, and whether `to` should be removed first,
+// None if it should be skipped
+pub fn resolve(from: &Path, to: &Path, policy: ConflictPolicy) -> Option<(PathBuf, bool)> {
+    match policy {
+        ConflictPolicy::Overwrite => Some((to.to_path_buf(), true)),
+        ConflictPolicy::Rename => Some((unique_path(to), false)),
+        ConflictPolicy::OverwriteIfNewer => {
+            let f = from.symlink_metadata().ok().and_then(|it| modified(&it));
+            let t = to.symlink_metadata().ok().and_then(|it| modified(&it));
+            match (f, t) {
+                (Some(f), Some(t)) if f > t => Some((to.to_path_buf(), true)),
+                _ => None,
+            }
+        }
+        ConflictPolicy::Skip | ConflictPolicy::Ask => None,
+    }
+}
+
+// the first of `name (1).ext`, `name (2).ext`... which does not exist
+pub fn unique_path(path: &Path) -> PathBuf {
+    let stem = path
+        .file_stem()
+        .map_or("".into(), |it| it.to_string_lossy())
+        .to_string();
+    let ext = path
+        .extension()
+        .map_or("".to_string(), |it| format!(".{}", it.to_string_lossy()));
+    let parent = path.parent().unwrap_or_else(|| Path::new("/"));
+
+    (1..)
+        .map(|i| parent.join(format!("{} ({}){}", stem, i, ext)))
+        .find(|it| it.symlink_metadata().is_err())
+        .unwrap()
+}
+
+fn desc(path: &Path) -> String {
+    match path.symlink_metadata() {
+        Ok(m) => {
+            let time = modified(&m).map_or("-".to_string(), |it| {
+                let dt: DateTime<Local> = it.into();
+                dt.format("%Y-%m-%d %H:%M:%S").to_string()
+            });
+            format!("{} {}", readable_size(m.len()), time)
+        }
+        Err(_) => "-".to_string(),
+    }
+}
+
+// the sizes and the modified times of both files, to help choosing the policy
+pub fn compare(from: &Path, to: &Path) -> String {
+    format!("new: {}, old: {}", desc(from), desc(to))
+}
+
+pub fn file_name(path: &Path) -> String {
+    path.file_name()
+        .map_or("".to_string(), |it| it.to_string_lossy().to_string())
+}
+
+// a rename which is left out for the error
+pub fn conflict_line(from: &Path, e: &Error) -> String {
+    match e {
+        Error::FileAlreadyExists(t) => {
+            format!("{} -> {} exists", file_name(from), file_name(Path::new(t)))
+        }
+        Error::InvalidPath(n) => format!("{} -> '{}' is invalid", file_name(from), n),
+        e => format!("{}: {}", file_name(from), error_desc(e)),
+    }
+}
+
+// the message of the files failed in an operation, None if none of them failed
+pub fn failure_message(failed: &[(PathBuf, Error)]) -> Option<String> {
+    let (f, e) = failed.first()?;
+    Some(format!(
+        "{} files failed, {}",
+        failed.len(),
+        conflict_line(f, e)
+    ))
+}
+
+// a readable description of the error for the status line
+pub fn error_desc(e: &Error) -> String {
+    match e {
+        Error::ReadOnly(m) => format!("{} is read only", m),
+        Error::InvalidArchive(m) => format!("Invalid archive {}", m),
+        Error::InvalidPattern(m) => format!("Invalid pattern {}", m),
+        Error::CommandFailed(m) => m.clone(),
+        Error::SpecialFile(m) => format!("{} can not be copied", m),
+        Error::InvalidAttribute(m) => format!("Invalid attribute {}", m),
+        e => format!("{:?}", e),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+
+    #[test]
+    fn unique_paths() {
+        let dir = std::env::temp_dir().join(format!("fff-conflict-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        fs::write(dir.join("a.txt"), "").unwrap();
+        assert_eq!(unique_path(&dir.join("a.txt")), dir.join("a (1).txt"));
+        fs::write(dir.join("a (1).txt"), "").unwrap();
+        assert_eq!(unique_path(&dir.join("a.txt")), dir.join("a (2).txt"));
+        assert_eq!(unique_path(&dir.join("b")), dir.join("b (1)"));
+        assert_eq!(unique_path(&dir.join(".hidden")), dir.join(".hidden (1)"));
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn conflict_lines() {
+        let e = Error::FileAlreadyExists("/d/b".to_string());
+        assert_eq!(conflict_line(Path::new("/d/a"), &e), "a -> b exists");
+        let e = Error::InvalidPath("".to_string());
+        assert_eq!(conflict_line(Path::new("/d/a"), &e), "a -> '' is invalid");
+        let e = Error::ReadOnly("/z".to_string());
+        assert_eq!(conflict_line(Path::new("/d/a"), &e), "a: /z is read only");
+    }
+}
@@ -0,0 +1,106 @@
+// The recursive disk usage of the local dirs. It is cached by the path and the modified time of
+// the dir, so the total is computed again after an entry of the dir itself is changed.
+use crate::kbd::action::DIR_SIZE_ACTION;
+use crate::model::context::Context;
+use crate::model::file::FileInfo;
+use crate::model::result::{Error, Res, Void};
+use crate::model::task::Progress;
+use lazy_static::lazy_static;
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::os::unix::fs::MetadataExt;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::SystemTime;
+
+// the st_blocks are in 512 bytes on all the unixes
+const BLOCK_SIZE: u64 = 512;
+
+lazy_static! {
+    static ref SIZES: Mutex<HashMap<PathBuf, (SystemTime, u64)>> = Mutex::new(HashMap::new());
+}
+
+// a DIR_SIZE_ACTION is queued and not handled yet, the totals computed meanwhile are shown by it
+static PENDING: AtomicBool = AtomicBool::new(false);
+
+// whether the total of the file can be computed, only the local dirs which are not links
+pub fn computable(info: &FileInfo) -> bool {
+    info.is_dir && info.protocol.is_none() && info.link.is_none()
+}
+
+// the computed total of the dir, None if it is not computed or the dir is changed since
+pub fn cached(info: &FileInfo) -> Option<u64> {
+    if !computable(info) {
+        return None;
+    }
+    let modified = info.modified?;
+    match SIZES.lock().unwrap().get(&info.path) {
+        Some((t, size)) if *t == modified => Some(*size),
+        _ => None,
+    }
+}
+
+// the size to show and to sort by, the computed total for the dirs
+pub fn size(info: &FileInfo) -> u64 {
+    cached(info).unwrap_or(info.size)
+}
+
+// the disk usage of the dir and all the files in it, the hard links are counted once,
+// the entries which can not be read are skipped
+pub fn compute(path: &Path, p: &Progress) -> Res<u64> {
+    let meta = path.symlink_metadata()?;
+    let mut total = meta.blocks() * BLOCK_SIZE;
+    let mut seen = HashSet::new();
+    let mut stack = vec![path.to_path_buf()];
+    while let Some(dir) = stack.pop() {
+        p.check()?;
+        let entries = match fs::read_dir(&dir) {
+            Ok(rd) => rd,
+            Err(e) => {
+                log::debug!("skip {}: {:?}", dir.display(), e);
+                continue;
+            }
+        };
+        for entry in entries.filter_map(|it| it.ok()) {
+            let m = match entry.path().symlink_metadata() {
+                Ok(m) => m,
+                Err(_) => continue,
+            };
+            if m.nlink() > 1 && !m.is_dir() && !seen.insert((m.dev(), m.ino())) {
+                continue;
+            }
+            total += m.blocks() * BLOCK_SIZE;
+            if m.is_dir() {
+                stack.push(entry.path());
+            }
+        }
+    }
+
+    if let Ok(t) = meta.modified() {
+        SIZES.lock().unwrap().insert(path.to_path_buf(), (t, total));
+    }
+    Ok(total)
+}
+
+// compute the totals of the dirs one by one, the action loop is told to show them
+pub async fn compute_all(paths: Vec<PathBuf>, ctx: Arc<Context>, p: Arc<Progress>) -> Void {
+    p.add_total(0, paths.len() as u64);
+    for path in paths {
+        let p1 = p.clone();
+        let path1 = path.clone();
+        match tokio::task::spawn_blocking(move || compute(&path1, &p1)).await? {
+            Ok(_) if !PENDING.swap(true, Ordering::SeqCst) => ctx.send_action(DIR_SIZE_ACTION),
+            Ok(_) => {}
+            Err(Error::Cancelled) => return Err(Error::Cancelled),
+            Err(e) => log::error!("compute size of {} failed: {:?}", path.display(), e),
+        }
+        p.advance(0, 1);
+    }
+    Ok(())
+}
+
+// the queued DIR_SIZE_ACTION is handled, the totals computed after it need another one
+pub fn clear_pending() {
+    PENDING.store(false, Ordering::SeqCst);
+}
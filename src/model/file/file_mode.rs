@@ -17,13 +17,33 @@ fn find(mode: u32) -> char {
     }
 }
 
+// the special bit shown at the execute bit of each class, in lower case if it is executable
+static SPECIALS: [(u32, char); 3] = [
+    (0o4000, 'S'), // setuid
+    (0o2000, 'S'), // setgid
+    (0o1000, 'T'), // sticky
+];
+
 pub fn mode_string(mode: u32) -> String {
     let mut re = String::new();
     re.push(find(mode));
 
-    "rwxrwxrwx"
-        .chars()
-        .enumerate()
-        .for_each(|(i, it)| re.push(if mode & (1 << (8 - i)) != 0 { it } else { '-' }));
+    "rwxrwxrwx".chars().enumerate().for_each(|(i, it)| {
+        let set = mode & (1 << (8 - i)) != 0;
+        let special = if i % 3 == 2 {
+            SPECIALS
+                .get(i / 3)
+                .filter(|(bit, _)| mode & bit != 0)
+                .map(|it| it.1)
+        } else {
+            None
+        };
+        re.push(match (special, set) {
+            (Some(s), true) => s.to_ascii_lowercase(),
+            (Some(s), false) => s,
+            (None, true) => it,
+            (None, false) => '-',
+        });
+    });
     re
 }
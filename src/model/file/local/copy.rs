@@ -0,0 +1,165 @@
+use crate::model::result::{Error, Res, Void};
+use crate::model::task::Progress;
+use filetime::FileTime;
+use std::ffi::CString;
+use std::fs::{self, File};
+use std::io::{self, Read, Write};
+use std::os::unix::ffi::OsStrExt;
+use std::os::unix::fs::{symlink, FileTypeExt, PermissionsExt};
+use std::path::Path;
+
+const BUFFER_SIZE: usize = 64 * 1024;
+
+// the total size in bytes and the count of the files and the dirs at `path`
+pub fn measure(path: &Path) -> Res<(u64, u64)> {
+    let meta = path.symlink_metadata()?;
+    if !meta.is_dir() {
+        return Ok((meta.len(), 1));
+    }
+
+    let mut re = (0, 1);
+    for d in fs::read_dir(path)? {
+        let (b, n) = measure(&d?.path())?;
+        re = (re.0 + b, re.1 + n);
+    }
+    Ok(re)
+}
+
+// copy the file or the dir at `from` to `to` recursively, symlinks are copied as links,
+// fifos are created again, sockets are skipped and devices can not be copied,
+// modes and modified times are kept
+pub fn copy_all(from: &Path, to: &Path, progress: &Progress) -> Void {
+    if to.starts_with(from) {
+        return Err(Error::InvalidPath(to.display().to_string()));
+    }
+    progress.check()?;
+
+    let meta = from.symlink_metadata()?;
+    let ft = meta.file_type();
+    if ft.is_symlink() {
+        symlink(fs::read_link(from)?, to)?;
+        progress.advance(meta.len(), 1);
+        return Ok(());
+    }
+    if ft.is_socket() {
+        progress.advance(0, 1);
+        return Ok(());
+    }
+    if ft.is_block_device() || ft.is_char_device() {
+        return Err(Error::SpecialFile(from.display().to_string()));
+    }
+
+    if ft.is_dir() {
+        fs::create_dir(to)?;
+        for d in fs::read_dir(from)? {
+            let d = d?;
+            copy_all(&d.path(), &to.join(d.file_name()), progress)?;
+        }
+        progress.advance(0, 1);
+    } else if ft.is_fifo() {
+        make_fifo(to, meta.permissions().mode())?;
+        progress.advance(0, 1);
+    } else {
+        if let Err(e) = copy_file(from, to, progress) {
+            let _ = fs::remove_file(to);
+            return Err(e);
+        }
+        progress.advance(0, 1);
+    }
+
+    fs::set_permissions(to, meta.permissions())?;
+    // the times are set by the path, a fifo would block to be opened
+    let mtime = FileTime::from_last_modification_time(&meta);
+    filetime::set_symlink_file_times(to, FileTime::from_last_access_time(&meta), mtime)?;
+    Ok(())
+}
+
+fn make_fifo(path: &Path, mode: u32) -> Void {
+    let p = CString::new(path.as_os_str().as_bytes())
+        .map_err(|_| Error::InvalidPath(path.display().to_string()))?;
+    if unsafe { libc::mkfifo(p.as_ptr(), mode as libc::mode_t) } != 0 {
+        return Err(io::Error::last_os_error().into());
+    }
+    Ok(())
+}
+
+fn copy_file(from: &Path, to: &Path, progress: &Progress) -> Void {
+    let mut r = File::open(from)?;
+    let mut w = File::create(to)?;
+    let mut buf = vec![0; BUFFER_SIZE];
+    loop {
+        progress.check()?;
+        let n = r.read(&mut buf)?;
+        if n == 0 {
+            return Ok(());
+        }
+        w.write_all(&buf[..n])?;
+        progress.advance(n as u64, 0);
+    }
+}
+
+// move the file or the dir at `from` to `to`, it is copied then removed if they are not
+// on the same device, a copy left by a failed or cancelled move is removed
+pub fn move_all(from: &Path, to: &Path, progress: &Progress) -> Void {
+    if to.starts_with(from) {
+        return Err(Error::InvalidPath(to.display().to_string()));
+    }
+    progress.check()?;
+
+    let (bytes, items) = measure(from)?;
+    match fs::rename(from, to) {
+        Err(e) if e.raw_os_error() == Some(libc::EXDEV) => {
+            let existed = to.symlink_metadata().is_ok();
+            if let Err(e) = copy_all(from, to, progress) {
+                if !existed {
+                    let _ = remove_all(to);
+                }
+                return Err(e);
+            }
+            remove_all(from)
+        }
+        Err(e) => Err(e.into()),
+        Ok(_) => {
+            progress.advance(bytes, items);
+            Ok(())
+        }
+    }
+}
+
+// remove the file or the dir at `path`, a symlink is removed without touching its target
+pub fn remove_all(path: &Path) -> Void {
+    if path.symlink_metadata()?.is_dir() {
+        fs::remove_dir_all(path)?;
+    } else {
+        fs::remove_file(path)?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::os::unix::net::UnixListener;
+
+    #[test]
+    fn copy_special_files() {
+        let dir = std::env::temp_dir().join(format!("fff-copy-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        let from = dir.join("from");
+        fs::create_dir_all(&from).unwrap();
+        fs::write(from.join("file"), "content").unwrap();
+        make_fifo(&from.join("fifo"), 0o600).unwrap();
+        let _socket = UnixListener::bind(from.join("socket")).unwrap();
+
+        let to = dir.join("to");
+        copy_all(&from, &to, &Progress::new()).unwrap();
+        assert_eq!(fs::read_to_string(to.join("file")).unwrap(), "content");
+        let fifo = to.join("fifo").symlink_metadata().unwrap();
+        assert!(fifo.file_type().is_fifo());
+        assert!(to.join("socket").symlink_metadata().is_err());
+
+        let dev = copy_all(Path::new("/dev/null"), &dir.join("null"), &Progress::new());
+        assert!(matches!(dev, Err(Error::SpecialFile(_))));
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -2,6 +2,7 @@ use crate::model::context::Context;
 use crate::model::file::local::file;
 use crate::model::file::path::InnerPath;
 use crate::model::file::*;
+use crate::model::journal::Step;
 use crate::model::result::{Error, Res, Void};
 use async_trait::async_trait;
 use std::convert::TryFrom;
@@ -25,8 +26,11 @@ impl Op for LocalDir {
     async fn delete(&self, _: &Context) -> Void {
         file::delete(&self.0)
     }
-    async fn open(&self, _: &Context) -> Void {
-        file::open(&self.0)
+    async fn delete_permanently(&self, _: &Context) -> Void {
+        file::delete_permanently(&self.0)
+    }
+    async fn open(&self, ctx: &Context) -> Void {
+        file::open(&self.0, ctx).await
     }
 }
 
@@ -64,14 +68,37 @@ impl DirOp for LocalDir {
                 ctx.message("File is already exists.");
                 return Err(Error::FileAlreadyExists(p.display().to_string()));
             }
-            fs::File::create(p)?;
+            fs::File::create(&p)?;
+            ctx.record(
+                format!("New file {}", p.display()),
+                vec![Step::Create {
+                    path: p,
+                    dir: false,
+                }],
+            );
         }
         Ok(())
     }
 
     async fn new_dir(&self, ctx: &Context) -> Void {
         if let Some(name) = ctx.request_input("New Dir").await {
-            fs::create_dir_all(self.join_path(&name))?;
+            let p = self.join_path(&name);
+            // the top most dir created, undoing removes it with the dirs created inside
+            let top = p
+                .ancestors()
+                .take_while(|it| it.symlink_metadata().is_err())
+                .last()
+                .map(|it| it.to_path_buf());
+            fs::create_dir_all(&p)?;
+            if let Some(top) = top {
+                ctx.record(
+                    format!("New dir {}", p.display()),
+                    vec![Step::Create {
+                        path: top,
+                        dir: true,
+                    }],
+                );
+            }
         }
         Ok(())
     }
@@ -80,7 +107,8 @@ impl DirOp for LocalDir {
         make(InnerPath::try_from(&self.join_path(child_path))?)
     }
 
-    async fn shell(&self, _: &Context) -> Void {
+    async fn shell(&self, ctx: &Context, env: &[(String, String)]) -> Void {
+        ctx.shell(&self.0.path, env).await?;
         Ok(())
     }
 }
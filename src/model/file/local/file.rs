@@ -1,6 +1,10 @@
 use crate::model::context::Context;
+use crate::model::file::local::remove_all;
 use crate::model::file::path::InnerPath;
+use crate::model::file::trash;
 use crate::model::file::*;
+use crate::model::file::{conflict, opener};
+use crate::model::journal::Step;
 use crate::model::result::{Error, Res, Void};
 use async_trait::async_trait;
 use std::convert::TryFrom;
@@ -23,27 +27,63 @@ pub fn parent(info: &FileInfo) -> Res<InnerFile> {
 
 pub async fn rename(info: &FileInfo, ctx: &Context) -> Void {
     if let Some(name) = ctx.request_input("New file name").await {
-        if let Some(nn) = &info.path.parent().map(move |p| p.join(name)) {
-            if nn.exists() {
-                ctx.message("The new file name is already exists, rename failed.");
-            } else {
-                std::fs::rename(&info.path, nn)?;
-                ctx.message("Rename success.");
+        if name.is_empty() {
+            return Ok(());
+        }
+        if let Some(nn) = info.path.parent().map(move |p| p.join(name)) {
+            let mut to = nn.clone();
+            let mut steps = Vec::new();
+            if nn == info.path {
+                return Ok(());
+            }
+            if nn.symlink_metadata().is_ok() {
+                let policy = match ctx.conflict_policy(&info.path, &nn, &mut None).await {
+                    Some(v) => v,
+                    None => return Ok(()),
+                };
+                match conflict::resolve(&info.path, &nn, policy) {
+                    Some((p, overwrite)) => {
+                        // the overwritten file goes to the trash, so the rename can be undone
+                        if overwrite {
+                            let trashed = trash::trash(&p)?;
+                            steps.push(Step::Trash {
+                                from: p.clone(),
+                                trashed,
+                            });
+                        }
+                        to = p;
+                    }
+                    None => {
+                        ctx.message("The new file name is already exists, rename skipped.");
+                        return Ok(());
+                    }
+                }
             }
+            std::fs::rename(&info.path, &to)?;
+            let desc = format!("Rename {} to {}", info.path.display(), to.display());
+            steps.push(Step::Move {
+                from: info.path.clone(),
+                to,
+            });
+            ctx.record(desc, steps);
+            ctx.message("Rename success.");
         }
     }
     Ok(())
 }
 
-pub fn delete(_info: &FileInfo) -> Void {
+// the trashed files are recorded by the caller, so all the files of a delete are undone together
+pub fn delete(info: &FileInfo) -> Void {
+    trash::trash(&info.path)?;
     Ok(())
 }
 
-pub fn open(_info: &FileInfo) -> Void {
-    if cfg!(target_os = "linux") || cfg!(target_os = "freebsd") {
-    } else if cfg!(target_os = "macos") {
-    }
-    Ok(())
+pub fn delete_permanently(info: &FileInfo) -> Void {
+    remove_all(&info.path)
+}
+
+pub async fn open(info: &FileInfo, ctx: &Context) -> Void {
+    opener::open(&info.path, ctx).await
 }
 
 #[async_trait]
@@ -60,17 +100,22 @@ impl Op for LocalFile {
     async fn delete(&self, _: &Context) -> Void {
         delete(&self.0)
     }
-    async fn open(&self, _: &Context) -> Void {
-        open(&self.0)
+    async fn delete_permanently(&self, _: &Context) -> Void {
+        delete_permanently(&self.0)
+    }
+    async fn open(&self, ctx: &Context) -> Void {
+        open(&self.0, ctx).await
     }
 }
 
 #[async_trait]
 impl FileOp for LocalFile {
-    async fn view(&self, _: &Context) -> Void {
+    async fn view(&self, ctx: &Context) -> Void {
+        ctx.view(std::slice::from_ref(&self.0.path)).await?;
         Ok(())
     }
-    async fn edit(&self, _: &Context) -> Void {
+    async fn edit(&self, ctx: &Context) -> Void {
+        ctx.edit(std::slice::from_ref(&self.0.path)).await?;
         Ok(())
     }
 }
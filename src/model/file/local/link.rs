@@ -0,0 +1,57 @@
+use crate::model::result::{Error, Res, Void};
+use std::fs;
+use std::os::unix::fs::symlink;
+use std::path::{Component, Path, PathBuf};
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum LinkKind {
+    Absolute,
+    Relative,
+    Hard,
+}
+
+// the real path of the file, the file itself is not resolved if it is a symlink
+fn real_path(path: &Path) -> Res<PathBuf> {
+    let name = path
+        .file_name()
+        .ok_or_else(|| Error::InvalidPath(path.display().to_string()))?;
+    let parent = path.parent().unwrap_or_else(|| Path::new("/"));
+    Ok(fs::canonicalize(parent)?.join(name))
+}
+
+// the path of `target` relative to `dir`, both of them are resolved first, so the link works
+// even if the dir is reached through symlinks
+pub fn relative_path(target: &Path, dir: &Path) -> Res<PathBuf> {
+    let target = real_path(target)?;
+    let dir = fs::canonicalize(dir)?;
+    let ts: Vec<Component> = target.components().collect();
+    let ds: Vec<Component> = dir.components().collect();
+    let common = ts.iter().zip(ds.iter()).take_while(|(a, b)| a == b).count();
+
+    let mut re = PathBuf::new();
+    ds[common..].iter().for_each(|_| re.push(".."));
+    ts[common..].iter().for_each(|it| re.push(it));
+    Ok(re)
+}
+
+// the target written into the link at `path` to the file `target`, or the file itself for the
+// hard link
+pub fn link_target(target: &Path, path: &Path, kind: LinkKind) -> Res<PathBuf> {
+    match kind {
+        LinkKind::Absolute => real_path(target),
+        LinkKind::Relative => {
+            let dir = path.parent().unwrap_or_else(|| Path::new("/"));
+            relative_path(target, dir)
+        }
+        LinkKind::Hard => Ok(target.to_path_buf()),
+    }
+}
+
+pub fn link(target: &Path, path: &Path, hard: bool) -> Void {
+    if hard {
+        fs::hard_link(target, path)?;
+    } else {
+        symlink(target, path)?;
+    }
+    Ok(())
+}
@@ -1,29 +1,41 @@
-use crate::model::file::file_mode::mode_string;
 use crate::model::file::local::dir::LocalDir;
 use crate::model::file::local::file::LocalFile;
 use crate::model::file::path::InnerPath;
+use crate::model::file::stat::Stat;
 use crate::model::file::{FileInfo, InnerFile, LinkInfo};
 use crate::model::result::{option_from_result, Error, Res};
 use std::fs::{read_link, Metadata};
+use std::os::unix::fs::MetadataExt;
 use std::path::Path;
 
+mod copy;
 mod dir;
 mod file;
+mod link;
+
+pub use copy::{copy_all, measure, move_all, remove_all};
+pub use file::parent;
+pub use link::{link, link_target, LinkKind};
 
 pub fn make(inner: InnerPath) -> Res<InnerFile> {
-    let p = &inner.path;
-    if !p.exists() {
-        return Error::PathNotExists(inner.to_string()).res();
-    }
-    let meta = p.symlink_metadata()?;
-    let v = make_it(&meta, inner);
-    Ok(if meta.is_dir() {
+    let v = info(inner)?;
+    Ok(if v.is_dir {
         InnerFile::Dir(Box::new(LocalDir::new(v)))
     } else {
         InnerFile::File(Box::new(LocalFile::new(v)))
     })
 }
 
+pub fn info(inner: InnerPath) -> Res<FileInfo> {
+    let p = &inner.path;
+    // a broken link exists too
+    if p.symlink_metadata().is_err() {
+        return Error::PathNotExists(inner.to_string()).res();
+    }
+    let meta = p.symlink_metadata()?;
+    Ok(make_it(&meta, inner))
+}
+
 fn make_it(meta: &Metadata, inner: InnerPath) -> FileInfo {
     let path = &inner.path;
     let name = path
@@ -31,10 +43,11 @@ fn make_it(meta: &Metadata, inner: InnerPath) -> FileInfo {
         .map(|r| r.to_str().unwrap())
         .unwrap_or("-")
         .to_string();
-    let mode = mode_string(0);
-    let link = if meta.file_type().is_symlink() {
+    let is_link = meta.file_type().is_symlink();
+    let link = if is_link {
+        // the link is followed to check, as the target may be relative or a link too
+        let broken = !path.exists();
         option_from_result(read_link(path)).map(|p| {
-            let broken = !p.exists();
             let target = if p.is_relative() {
                 path.parent().unwrap_or(Path::new("/")).join(p)
             } else {
@@ -49,14 +62,18 @@ fn make_it(meta: &Metadata, inner: InnerPath) -> FileInfo {
         None
     };
 
+    // a link to a dir is listed as a dir, the link path is kept when it is entered
+    let is_dir = meta.is_dir() || (is_link && path.is_dir());
+
     FileInfo {
         path: path.clone(),
         inner,
         name,
         size: meta.len(),
-        mode,
+        mode: meta.mode(),
         modified: option_from_result(meta.modified()),
-        is_dir: meta.is_dir(),
+        stat: Some(Stat::from(meta)),
+        is_dir,
         link,
         protocol: None,
     }
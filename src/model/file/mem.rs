@@ -0,0 +1,518 @@
+// A file tree living entirely in memory, mounted on a host path as `/host@mem:///path`, the
+// host does not need to exist. It is used by the tests which should not touch the real disk,
+// and as the scratch dir of a local dir, whose content is kept until fff exits.
+use crate::model::context::Context;
+use crate::model::file::archive::{Index, DEFAULT_DIR_MODE, DEFAULT_FILE_MODE};
+use crate::model::file::path::InnerPath;
+use crate::model::file::protocol::{self, Protocol};
+use crate::model::file::{DirOp, FileInfo, FileOp, InnerFile, Op, ProtocolInfo};
+use crate::model::result::{Error, Res, Void};
+use async_trait::async_trait;
+use lazy_static::lazy_static;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::time::SystemTime;
+
+const NAME: &str = "mem";
+
+lazy_static! {
+    static ref FILESYSTEMS: Mutex<HashMap<PathBuf, MemFs>> = Mutex::new(HashMap::new());
+}
+
+pub struct MemProtocol;
+
+impl Protocol for MemProtocol {
+    fn name(&self) -> &'static str {
+        NAME
+    }
+
+    // memory file systems are mounted explicitly
+    fn support(&self, _: &InnerFile) -> bool {
+        false
+    }
+
+    fn create(&self, info: ProtocolInfo, path: &Path) -> Res<InnerFile> {
+        filesystem(&info.root.path).make(info, path)
+    }
+
+    // the host is only the name of the file system, it is not looked up on disk
+    fn root(&self, host: &Path) -> Res<FileInfo> {
+        Ok(FileInfo {
+            inner: InnerPath::new_protocol(host, NAME, Path::new("/")),
+            name: host
+                .file_name()
+                .map_or("/".to_string(), |it| it.to_string_lossy().to_string()),
+            path: host.to_path_buf(),
+            size: 0,
+            mode: DEFAULT_DIR_MODE,
+            modified: None,
+            stat: None,
+            is_dir: true,
+            link: None,
+            protocol: None,
+        })
+    }
+}
+
+// the memory file system mounted on `host`, it is created empty on first use
+pub fn mount(host: &Path) -> MemFs {
+    protocol::register(MemProtocol);
+    filesystem(host)
+}
+
+fn filesystem(host: &Path) -> MemFs {
+    FILESYSTEMS
+        .lock()
+        .unwrap()
+        .entry(host.to_path_buf())
+        .or_insert_with(|| MemFs::new(host))
+        .clone()
+}
+
+struct Node {
+    is_dir: bool,
+    content: Vec<u8>,
+    modified: SystemTime,
+}
+
+impl Node {
+    fn dir() -> Self {
+        Node {
+            is_dir: true,
+            content: Vec::new(),
+            modified: SystemTime::now(),
+        }
+    }
+
+    fn file(content: &[u8]) -> Self {
+        Node {
+            is_dir: false,
+            content: content.to_vec(),
+            modified: SystemTime::now(),
+        }
+    }
+}
+
+#[derive(Clone)]
+pub struct MemFs {
+    host: PathBuf,
+    nodes: Arc<Mutex<HashMap<PathBuf, Node>>>,
+}
+
+impl MemFs {
+    fn new(host: &Path) -> Self {
+        let mut nodes = HashMap::new();
+        nodes.insert(PathBuf::from("/"), Node::dir());
+        MemFs {
+            host: host.to_path_buf(),
+            nodes: Arc::new(Mutex::new(nodes)),
+        }
+    }
+
+    pub fn inner_path(&self, path: &str) -> InnerPath {
+        InnerPath::new_protocol(&self.host, NAME, &Index::normalize(path))
+    }
+
+    pub fn exists(&self, path: &str) -> bool {
+        self.nodes
+            .lock()
+            .unwrap()
+            .contains_key(&Index::normalize(path))
+    }
+
+    // create or overwrite the file at `path`, the parent dirs must exist
+    pub fn write(&self, path: &str, content: &[u8]) -> Void {
+        let p = Index::normalize(path);
+        let mut nodes = self.nodes.lock().unwrap();
+        check_parent(&nodes, &p)?;
+        match nodes.get(&p) {
+            Some(n) if n.is_dir => Err(Error::FileAlreadyExists(p.display().to_string())),
+            _ => {
+                nodes.insert(p, Node::file(content));
+                Ok(())
+            }
+        }
+    }
+
+    // create the dir at `path` and all its missing parents
+    pub fn create_dir_all(&self, path: &str) -> Void {
+        let p = Index::normalize(path);
+        let mut nodes = self.nodes.lock().unwrap();
+        for a in p.ancestors().collect::<Vec<_>>().into_iter().rev() {
+            match nodes.get(a) {
+                Some(n) if !n.is_dir => {
+                    return Err(Error::FileAlreadyExists(a.display().to_string()))
+                }
+                Some(_) => {}
+                None => {
+                    nodes.insert(a.to_path_buf(), Node::dir());
+                }
+            }
+        }
+        Ok(())
+    }
+
+    // remove the file or the dir at `path` with all its content
+    pub fn remove(&self, path: &str) -> Void {
+        let p = Index::normalize(path);
+        if p == Path::new("/") {
+            return Err(Error::InvalidPath(p.display().to_string()));
+        }
+        let mut nodes = self.nodes.lock().unwrap();
+        if !nodes.contains_key(&p) {
+            return Err(Error::PathNotExists(p.display().to_string()));
+        }
+        nodes.retain(|k, _| !k.starts_with(&p));
+        touch_parent(&mut nodes, &p);
+        Ok(())
+    }
+
+    // move the file or the dir at `from` to `to`, `to` must not exist
+    pub fn rename(&self, from: &str, to: &str) -> Void {
+        let (f, t) = (Index::normalize(from), Index::normalize(to));
+        let mut nodes = self.nodes.lock().unwrap();
+        check_target(&nodes, &f, &t)?;
+
+        let moved: Vec<_> = nodes
+            .keys()
+            .filter(|it| it.starts_with(&f))
+            .cloned()
+            .collect();
+        for k in moved {
+            if let Some(n) = nodes.remove(&k) {
+                let nk = t.join(k.strip_prefix(&f).unwrap());
+                nodes.insert(nk, n);
+            }
+        }
+        touch_parent(&mut nodes, &f);
+        touch_parent(&mut nodes, &t);
+        Ok(())
+    }
+
+    fn children(&self, path: &Path) -> Vec<PathBuf> {
+        let mut re: Vec<_> = self
+            .nodes
+            .lock()
+            .unwrap()
+            .keys()
+            .filter(|it| it.parent() == Some(path))
+            .cloned()
+            .collect();
+        re.sort();
+        re
+    }
+
+    fn make(&self, info: ProtocolInfo, path: &Path) -> Res<InnerFile> {
+        let p = Index::normalize(&path.display().to_string());
+        let mut fi = {
+            let nodes = self.nodes.lock().unwrap();
+            let node = match nodes.get(&p) {
+                Some(v) => v,
+                None => {
+                    return Err(Error::PathNotExists(
+                        InnerPath::new_protocol(&self.host, NAME, &p).to_string(),
+                    ))
+                }
+            };
+            let mut fi = protocol::file_info(&info, &p, node.is_dir);
+            fi.size = node.content.len() as u64;
+            fi.modified = Some(node.modified);
+            fi
+        };
+        fi.mode = if fi.is_dir {
+            DEFAULT_DIR_MODE
+        } else {
+            DEFAULT_FILE_MODE
+        };
+
+        let file = MemFile {
+            info: fi,
+            fs: self.clone(),
+        };
+        Ok(if file.info.is_dir {
+            InnerFile::Dir(Box::new(file))
+        } else {
+            InnerFile::File(Box::new(file))
+        })
+    }
+}
+
+// the helpers to prepare and check the content in the tests
+#[cfg(test)]
+impl MemFs {
+    // the file at `path`, created through the protocol registry like any other protocol file
+    pub fn get(&self, path: &str) -> Res<InnerFile> {
+        protocol::make(self.inner_path(path))
+    }
+
+    pub fn root(&self) -> Res<InnerFile> {
+        self.get("/")
+    }
+
+    pub fn read(&self, path: &str) -> Res<Vec<u8>> {
+        let p = Index::normalize(path);
+        match self.nodes.lock().unwrap().get(&p) {
+            Some(n) if !n.is_dir => Ok(n.content.clone()),
+            Some(_) => Err(Error::InvalidPath(p.display().to_string())),
+            None => Err(Error::PathNotExists(p.display().to_string())),
+        }
+    }
+
+    // copy the file or the dir at `from` to `to` with all its content, `to` must not exist
+    pub fn copy(&self, from: &str, to: &str) -> Void {
+        let (f, t) = (Index::normalize(from), Index::normalize(to));
+        let mut nodes = self.nodes.lock().unwrap();
+        check_target(&nodes, &f, &t)?;
+
+        let copied: Vec<_> = nodes
+            .iter()
+            .filter(|(k, _)| k.starts_with(&f))
+            .map(|(k, n)| {
+                let node = Node {
+                    is_dir: n.is_dir,
+                    content: n.content.clone(),
+                    modified: SystemTime::now(),
+                };
+                (t.join(k.strip_prefix(&f).unwrap()), node)
+            })
+            .collect();
+        nodes.extend(copied);
+        touch_parent(&mut nodes, &t);
+        Ok(())
+    }
+}
+
+// drop the memory file system mounted on `host` with all its content
+#[cfg(test)]
+pub fn unmount(host: &Path) {
+    FILESYSTEMS.lock().unwrap().remove(host);
+}
+
+fn check_parent(nodes: &HashMap<PathBuf, Node>, path: &Path) -> Void {
+    let parent = path.parent().unwrap_or_else(|| Path::new("/"));
+    match nodes.get(parent) {
+        Some(n) if n.is_dir => Ok(()),
+        Some(_) => Err(Error::DirIsRequired(parent.display().to_string())),
+        None => Err(Error::PathNotExists(parent.display().to_string())),
+    }
+}
+
+// `from` can be copied or moved to `to`
+fn check_target(nodes: &HashMap<PathBuf, Node>, from: &Path, to: &Path) -> Void {
+    if !nodes.contains_key(from) {
+        return Err(Error::PathNotExists(from.display().to_string()));
+    }
+    if nodes.contains_key(to) {
+        return Err(Error::FileAlreadyExists(to.display().to_string()));
+    }
+    if to.starts_with(from) {
+        return Err(Error::InvalidPath(to.display().to_string()));
+    }
+    check_parent(nodes, to)
+}
+
+// the modified time of a dir changes when its children are changed
+fn touch_parent(nodes: &mut HashMap<PathBuf, Node>, path: &Path) {
+    if let Some(n) = path.parent().and_then(|it| nodes.get_mut(it)) {
+        n.modified = SystemTime::now();
+    }
+}
+
+// a file or a dir, the type is decided by the `InnerFile` variant holding it
+pub struct MemFile {
+    info: FileInfo,
+    fs: MemFs,
+}
+
+impl MemFile {
+    fn path_str(&self) -> String {
+        self.info.path.display().to_string()
+    }
+
+    fn join_path(&self, name: &str) -> String {
+        self.info.path.join(name).display().to_string()
+    }
+}
+
+#[async_trait]
+impl Op for MemFile {
+    fn get(&self) -> &FileInfo {
+        &self.info
+    }
+    async fn parent(&self, _: &Context) -> Res<InnerFile> {
+        protocol::parent(&self.info)
+    }
+    async fn rename(&self, ctx: &Context) -> Void {
+        if let Some(name) = ctx.request_input("New file name").await {
+            if let Some(nn) = self.info.path.parent().map(|p| p.join(name)) {
+                let nn = nn.display().to_string();
+                if self.fs.exists(&nn) {
+                    ctx.message("The new file name is already exists, rename failed.");
+                } else {
+                    self.fs.rename(&self.path_str(), &nn)?;
+                    ctx.message("Rename success.");
+                }
+            }
+        }
+        Ok(())
+    }
+    // there is no trash in memory, both deletes remove the file
+    async fn delete(&self, _: &Context) -> Void {
+        self.fs.remove(&self.path_str())
+    }
+    async fn delete_permanently(&self, _: &Context) -> Void {
+        self.fs.remove(&self.path_str())
+    }
+    async fn open(&self, _: &Context) -> Void {
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl FileOp for MemFile {
+    async fn view(&self, _: &Context) -> Void {
+        Ok(())
+    }
+    async fn edit(&self, _: &Context) -> Void {
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl DirOp for MemFile {
+    async fn list(&self, _: &Context) -> Res<Vec<InnerFile>> {
+        let pi = self.info.protocol.as_ref().unwrap();
+        self.fs
+            .children(&self.info.path)
+            .iter()
+            .map(|it| self.fs.make(pi.clone(), it))
+            .collect()
+    }
+
+    async fn new_file(&self, ctx: &Context) -> Void {
+        if let Some(name) = ctx.request_input("New File").await {
+            let p = self.join_path(&name);
+            if self.fs.exists(&p) {
+                ctx.message("File is already exists.");
+                return Err(Error::FileAlreadyExists(p));
+            }
+            self.fs.write(&p, &[])?;
+        }
+        Ok(())
+    }
+
+    async fn new_dir(&self, ctx: &Context) -> Void {
+        if let Some(name) = ctx.request_input("New Dir").await {
+            self.fs.create_dir_all(&self.join_path(&name))?;
+        }
+        Ok(())
+    }
+
+    async fn goto(&self, _: &Context, child_path: &str) -> Res<InnerFile> {
+        let pi = self.info.protocol.as_ref().unwrap();
+        self.fs.make(pi.clone(), &self.info.path.join(child_path))
+    }
+
+    async fn shell(&self, _: &Context, _: &[(String, String)]) -> Void {
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn names(files: &[InnerFile]) -> Vec<String> {
+        files.iter().map(|it| it.info().name.clone()).collect()
+    }
+
+    async fn list(fs: &MemFs, ctx: &Context, path: &str) -> Vec<String> {
+        match fs.get(path).unwrap() {
+            InnerFile::Dir(d) => names(&d.list(ctx).await.unwrap()),
+            InnerFile::File(_) => panic!("{} is not a dir", path),
+        }
+    }
+
+    fn sample(host: &str) -> MemFs {
+        let fs = mount(Path::new(host));
+        fs.create_dir_all("/a/b").unwrap();
+        fs.write("/a/one.txt", b"one").unwrap();
+        fs.write("/a/b/two.txt", b"two").unwrap();
+        fs
+    }
+
+    #[test]
+    fn mount_without_host_file() {
+        let fs = mount(Path::new("/no/such/host"));
+        let root = fs.root().unwrap();
+        assert!(root.is_dir());
+        assert_eq!(
+            root.info().inner.to_string(),
+            fs.inner_path("/").to_string()
+        );
+        unmount(Path::new("/no/such/host"));
+    }
+
+    #[tokio::test]
+    async fn list_dirs() {
+        let fs = sample("/mem/list");
+        let ctx = Context::for_test(Path::new("/no/such/home"));
+        assert_eq!(list(&fs, &ctx, "/").await, vec!["a"]);
+        assert_eq!(list(&fs, &ctx, "/a").await, vec!["b", "one.txt"]);
+        assert_eq!(list(&fs, &ctx, "a/b/").await, vec!["two.txt"]);
+        assert_eq!(fs.get("/a/one.txt").unwrap().info().size, 3);
+        unmount(Path::new("/mem/list"));
+    }
+
+    #[test]
+    fn copy_files() {
+        let fs = sample("/mem/copy");
+        fs.copy("/a", "/c").unwrap();
+        assert_eq!(fs.read("/c/b/two.txt").unwrap(), b"two");
+        assert_eq!(fs.read("/a/b/two.txt").unwrap(), b"two");
+
+        assert!(fs.copy("/a", "/c").is_err());
+        assert!(fs.copy("/a", "/a/b/a").is_err());
+        assert!(fs.copy("/x", "/y").is_err());
+        unmount(Path::new("/mem/copy"));
+    }
+
+    #[test]
+    fn rename_files() {
+        let fs = sample("/mem/rename");
+        fs.rename("/a/b", "/b").unwrap();
+        assert!(!fs.exists("/a/b/two.txt"));
+        assert_eq!(fs.read("/b/two.txt").unwrap(), b"two");
+
+        assert!(fs.rename("/a/one.txt", "/b/two.txt").is_err());
+        assert!(fs.rename("/a/one.txt", "/missing/one.txt").is_err());
+        assert!(fs.rename("/a/one.txt", "/a/one.txt/x").is_err());
+        unmount(Path::new("/mem/rename"));
+    }
+
+    #[tokio::test]
+    async fn delete_files() {
+        let fs = sample("/mem/delete");
+        let ctx = Context::for_test(Path::new("/no/such/home"));
+        fs.get("/a/b").unwrap().delete(&ctx).await.unwrap();
+        assert!(!fs.exists("/a/b"));
+        assert!(!fs.exists("/a/b/two.txt"));
+        assert!(fs.exists("/a/one.txt"));
+
+        let f = fs.get("/a/one.txt").unwrap();
+        f.delete_permanently(&ctx).await.unwrap();
+        assert_eq!(list(&fs, &ctx, "/a").await, Vec::<String>::new());
+        assert!(fs.remove("/").is_err());
+        unmount(Path::new("/mem/delete"));
+    }
+
+    #[test]
+    fn unmount_drops_content() {
+        let fs = sample("/mem/unmount");
+        assert!(fs.exists("/a/one.txt"));
+        unmount(Path::new("/mem/unmount"));
+        assert!(!mount(Path::new("/mem/unmount")).exists("/a/one.txt"));
+        unmount(Path::new("/mem/unmount"));
+    }
+}
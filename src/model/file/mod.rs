@@ -1,40 +1,67 @@
 use crate::model::context::Context;
 use crate::model::file::path::InnerPath;
+use crate::model::file::stat::Stat;
 use crate::model::result::{Error, Res, Void};
+pub use archive::git::revision_path;
 use async_trait::async_trait;
 use chrono::{DateTime, Local};
-pub use local::make;
+pub use local::{copy_all, link, link_target, make, measure, move_all, remove_all, LinkKind};
 use std::convert::TryFrom;
 use std::path::PathBuf;
 use std::sync::Arc;
 use std::time::SystemTime;
 
-mod cmd;
-mod file_mode;
+pub mod archive;
+pub mod attr;
+pub mod cmd;
+pub mod conflict;
+pub mod dir_size;
+pub mod file_mode;
 mod local;
+pub mod mem;
+pub mod opener;
 pub mod path;
-mod protocol;
+pub mod pattern;
+pub mod protocol;
+pub mod rename;
+pub mod stat;
+pub mod trash;
+
+// register the builtin protocols
+pub fn init() {
+    protocol::register(archive::zip::ZipProtocol::new());
+    protocol::register(archive::tar::TarProtocol::new());
+    protocol::register(archive::git::GitProtocol::new());
+    protocol::register(trash::TrashProtocol);
+    protocol::register(mem::MemProtocol);
+}
 
+#[derive(Clone)]
 pub struct FileInfo {
     pub inner: InnerPath,
     pub name: String,
     pub path: PathBuf,
     pub size: u64,
-    pub mode: String,
+    // the st_mode with the file type bits
+    pub mode: u32,
     pub modified: Option<SystemTime>,
+    // None if the file is not on disk
+    pub stat: Option<Stat>,
     pub is_dir: bool,
     pub link: Option<LinkInfo>,
     pub protocol: Option<ProtocolInfo>,
 }
 
+#[derive(Clone)]
 pub struct LinkInfo {
     pub broken: bool,
     pub target: String,
 }
 
+#[derive(Clone)]
 pub struct ProtocolInfo {
     pub protocol: String,
-    pub instance_id: u8,
+    pub instance_id: u64,
     pub root: Box<Arc<FileInfo>>,
 }
 
@@ -71,49 +98,59 @@ impl InnerFile {
     }
 
     pub fn readable_size(&self) -> String {
-        let info = self.info();
-        let mut unit = "B";
-        let base = 1024f64;
-        let mut size = info.size as f64;
-
-        if size > base {
-            unit = "K";
-            size = size / base;
-        } else {
-            return format!("{}{}", info.size, unit);
-        }
+        readable_size(dir_size::size(self.info()))
+    }
 
-        if size > base {
-            unit = "M";
-            size = size / base;
-        }
+    pub fn modify_time_str(&self) -> String {
+        time_str(self.info().modified)
+    }
 
-        if size > base {
-            unit = "G";
-            size = size / base;
+    // the time the metadata is last changed, the ctime
+    pub fn change_time_str(&self) -> String {
+        time_str(self.info().stat.as_ref().and_then(|it| it.changed))
+    }
+}
+
+fn time_str(t: Option<SystemTime>) -> String {
+    match t {
+        Some(v) => {
+            let dt: DateTime<Local> = v.into();
+            dt.format("%Y-%m-%d %H:%M:%S").to_string()
         }
+        None => format!("{0:^19}", "-"),
+    }
+}
 
-        return format!("{0:.2}{1}", size, unit);
+pub fn readable_size(bytes: u64) -> String {
+    let mut unit = "B";
+    let base = 1024f64;
+    let mut size = bytes as f64;
+
+    if size > base {
+        unit = "K";
+        size = size / base;
+    } else {
+        return format!("{}{}", bytes, unit);
     }
 
-    pub fn modify_time_str(&self) -> String {
-        let info = self.info();
-        match &info.modified {
-            Some(v) => {
-                let dt: DateTime<Local> = v.clone().into();
-                dt.format("%Y-%m-%d %H:%M:%S").to_string()
-            }
-            None => format!("{0:^19}", "-"),
-        }
+    if size > base {
+        unit = "M";
+        size = size / base;
     }
+
+    if size > base {
+        unit = "G";
+        size = size / base;
+    }
+
+    return format!("{0:.2}{1}", size, unit);
 }
 
 impl TryFrom<InnerPath> for InnerFile {
     type Error = Error;
 
     fn try_from(value: InnerPath) -> Res<Self> {
-        // TODO check protocol
-        return make(value);
+        protocol::make(value)
     }
 }
 
@@ -150,6 +187,14 @@ impl Op for InnerFile {
         .await
     }
 
+    async fn delete_permanently(&self, context: &Context) -> Void {
+        match self {
+            InnerFile::File(v) => v.delete_permanently(context),
+            InnerFile::Dir(v) => v.delete_permanently(context),
+        }
+        .await
+    }
+
     async fn open(&self, context: &Context) -> Void {
         match self {
             InnerFile::File(v) => v.open(context),
@@ -165,7 +210,9 @@ pub trait Op {
     fn get(&self) -> &FileInfo;
     async fn parent(&self, context: &Context) -> Res<InnerFile>;
     async fn rename(&self, context: &Context) -> Void;
+    // move the file to the trash if the backend has one
     async fn delete(&self, context: &Context) -> Void;
+    async fn delete_permanently(&self, context: &Context) -> Void;
     async fn open(&self, context: &Context) -> Void;
 }
 
@@ -181,5 +228,6 @@ pub trait DirOp: Op {
     async fn new_file(&self, context: &Context) -> Void;
     async fn new_dir(&self, context: &Context) -> Void;
     async fn goto(&self, context: &Context, child_path: &str) -> Res<InnerFile>;
-    async fn shell(&self, context: &Context) -> Void;
+    // run the shell in the dir, with the extra environment variables
+    async fn shell(&self, context: &Context, env: &[(String, String)]) -> Void;
 }
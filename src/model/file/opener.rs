@@ -0,0 +1,183 @@
+// Open files by the opener rules of the config, in the spirit of the rifle of ranger. The rules
+// are tried in order, then the text files are opened by the configured editor and pager, and
+// xdg-open, or open on macOS, is the last rule for all the files.
+use crate::config::{Config, OpenRule};
+use crate::model::context::Context;
+use crate::model::file::cmd::{self, Placeholders};
+use crate::model::file::pattern::glob_regex;
+use crate::model::result::{Error, Void};
+use std::io;
+use std::os::unix::process::CommandExt;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+
+// the MIME type of the file given by the `file` command, None if it is unknown
+fn mime(path: &Path) -> Option<String> {
+    let out = Command::new("file")
+        .args(["--brief", "--mime-type", "--"])
+        .arg(path)
+        .stdin(Stdio::null())
+        .output()
+        .ok()?;
+    if !out.status.success() {
+        return None;
+    }
+    Some(String::from_utf8_lossy(&out.stdout).trim().to_string())
+}
+
+fn glob_match(glob: &str, s: &str) -> bool {
+    match glob_regex(glob) {
+        Ok(r) => r.is_match(s),
+        Err(e) => {
+            log::error!("invalid opener glob {}: {:?}", glob, e);
+            false
+        }
+    }
+}
+
+// the MIME type is only detected if a rule needs it, and only once
+fn matches(rule: &OpenRule, path: &Path, detected: &mut Option<Option<String>>) -> bool {
+    if !rule.ext.is_empty() {
+        let ext = path.extension().map(|it| it.to_string_lossy());
+        match ext {
+            Some(e) if rule.ext.iter().any(|it| it.eq_ignore_ascii_case(&e)) => {}
+            _ => return false,
+        }
+    }
+    if let Some(g) = &rule.glob {
+        let target = if g.contains('/') {
+            path.display().to_string()
+        } else {
+            path.file_name()
+                .map_or("".to_string(), |it| it.to_string_lossy().to_string())
+        };
+        if !glob_match(g, &target) {
+            return false;
+        }
+    }
+    if let Some(m) = &rule.mime {
+        match detected.get_or_insert_with(|| mime(path)) {
+            Some(t) if glob_match(m, t) => {}
+            _ => return false,
+        }
+    }
+    true
+}
+
+fn rule(mime: Option<&str>, run: &str, fork: bool) -> OpenRule {
+    OpenRule {
+        ext: Vec::new(),
+        mime: mime.map(|it| it.to_string()),
+        glob: None,
+        run: run.to_string(),
+        fork,
+    }
+}
+
+// the rules after the ones of the config, the editor and the pager for the text files, then
+// xdg-open for all the files
+fn defaults(config: &Config) -> Vec<OpenRule> {
+    let fallback = if cfg!(target_os = "macos") {
+        "open"
+    } else {
+        "xdg-open"
+    };
+    vec![
+        rule(Some("text/*"), &config.editor, false),
+        rule(Some("inode/x-empty"), &config.editor, false),
+        rule(Some("text/*"), &config.pager, false),
+        rule(None, fallback, true),
+    ]
+}
+
+// the rules matching the file in order, the fallback is the last one
+pub fn rules(ctx: &Context, path: &Path) -> Vec<OpenRule> {
+    let mut detected = None;
+    let config = ctx.config();
+    config
+        .opener
+        .iter()
+        .chain(defaults(config).iter())
+        .filter(|it| matches(it, path, &mut detected))
+        .cloned()
+        .collect()
+}
+
+// the shell line of the rule for the file, the file is appended if the line has no placeholder
+fn line(rule: &OpenRule, path: &Path) -> String {
+    let dir = path.parent().map_or(PathBuf::from("/"), Path::to_path_buf);
+    let ph = Placeholders {
+        selected: Some(path.to_path_buf()),
+        marked: vec![path.to_path_buf()],
+        dir: dir.clone(),
+        other_dir: dir,
+    };
+    if rule.run.contains('%') {
+        cmd::expand(&rule.run, &ph)
+    } else {
+        cmd::expand(&format!("{} %f", rule.run), &ph)
+    }
+}
+
+pub async fn open_with(rule: &OpenRule, path: &Path, ctx: &Context) -> Void {
+    let dir = path.parent().unwrap_or_else(|| Path::new("/"));
+    let mut c = cmd::command(&line(rule, path), dir);
+    if rule.fork {
+        // it gets its own session, so it is not bound to the terminal of fff
+        unsafe {
+            c.pre_exec(|| {
+                if libc::setsid() == -1 {
+                    return Err(io::Error::last_os_error());
+                }
+                Ok(())
+            });
+        }
+        let mut child = c
+            .stdin(Stdio::null())
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .spawn()?;
+        // reap it after it exits
+        std::thread::spawn(move || child.wait());
+        return Ok(());
+    }
+
+    let status = ctx.run(c).await?;
+    if !status.success() {
+        return Err(Error::CommandFailed(format!(
+            "{} exited with {}",
+            rule.run, status
+        )));
+    }
+    Ok(())
+}
+
+// open the file by the first matching rule
+pub async fn open(path: &Path, ctx: &Context) -> Void {
+    let rules = rules(ctx, path);
+    open_with(&rules[0], path, ctx).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn defaults_follow_config() {
+        let mut config = Config::new(&PathBuf::from("/no/such/home"));
+        config.editor = "nvim".to_string();
+        config.pager = "bat".to_string();
+        let runs: Vec<_> = defaults(&config).into_iter().map(|it| it.run).collect();
+        assert_eq!(&runs[..3], ["nvim", "nvim", "bat"]);
+
+        let mut detected = Some(Some("text/plain".to_string()));
+        let p = Path::new("/a.txt");
+        let matched: Vec<_> = defaults(&config)
+            .into_iter()
+            .filter(|it| matches(it, p, &mut detected))
+            .map(|it| it.run)
+            .collect();
+        assert_eq!(matched.len(), 3);
+        assert_eq!(matched[1], "bat");
+    }
+}
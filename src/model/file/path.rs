@@ -19,7 +19,7 @@ pub struct InnerPath {
 
 impl InnerPath {
     fn from_path(value: PathBuf) -> Result<Self, Error> {
-        if !value.exists() {
+        if value.symlink_metadata().is_err() {
             return Err(Error::PathNotExists(value.display().to_string()));
         }
 
@@ -29,6 +29,17 @@ impl InnerPath {
             protocol: None,
         });
     }
+
+    pub fn new_protocol(host: &Path, protocol: &str, path: &Path) -> Self {
+        InnerPath {
+            origin: format!("{}@{}://{}", host.display(), protocol, path.display()),
+            path: host.to_path_buf(),
+            protocol: Some(PathProtocolInfo {
+                protocol: protocol.to_string(),
+                path: path.to_path_buf(),
+            }),
+        }
+    }
 }
 
 impl TryFrom<&PathBuf> for InnerPath {
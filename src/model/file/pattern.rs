@@ -0,0 +1,347 @@
+// Patterns to rename many files at once, there are three forms:
+//
+//   s/REGEX/TEMPLATE/FLAGS   replace the first match of the regex, `g` for all, `i` to ignore case
+//   GLOB => TEMPLATE         rename the files matching the glob, `*` and `?` are captured
+//   TEMPLATE                 rename all the files
+//
+// A template is the new name with `{..}` placeholders, `{{` and `}}` for the braces:
+//
+//   {name} {stem} {ext}      the name, the name without extension, and the extension
+//   {0} {1} ...              the whole match and the captured groups
+//   {n} {n:03}               the counter from 1, optionally padded by zeros
+//   {mtime} {mtime:%Y-%m}    the modified time, `%Y%m%d` by default
+//
+// Every placeholder can be transformed by `|upper`, `|lower` or `|title`, e.g. `{stem|lower}`.
+use crate::model::result::{Error, Res};
+use chrono::format::{Item, StrftimeItems};
+use chrono::{DateTime, Local};
+use regex::{Captures, Regex, RegexBuilder};
+use std::path::Path;
+use std::time::SystemTime;
+
+const DEFAULT_TIME_FORMAT: &str = "%Y%m%d";
+
+enum Case {
+    Keep,
+    Upper,
+    Lower,
+    Title,
+}
+
+enum Key {
+    Name,
+    Stem,
+    Ext,
+    Group(usize),
+    Counter(usize),
+    Mtime(String),
+}
+
+enum Part {
+    Text(String),
+    Holder(Key, Case),
+}
+
+enum Matcher {
+    All,
+    Glob(Regex),
+    Regex(Regex, bool),
+}
+
+pub struct Pattern {
+    matcher: Matcher,
+    template: Vec<Part>,
+}
+
+// the file to rename
+pub struct Subject<'a> {
+    pub name: &'a str,
+    pub modified: Option<SystemTime>,
+}
+
+fn invalid(pattern: &str, reason: &str) -> Error {
+    Error::InvalidPattern(format!("{}: {}", pattern, reason))
+}
+
+fn parse_case(s: Option<&str>) -> Res<Case> {
+    Ok(match s {
+        None => Case::Keep,
+        Some("upper") => Case::Upper,
+        Some("lower") => Case::Lower,
+        Some("title") => Case::Title,
+        Some(c) => return Err(invalid(c, "unknown case")),
+    })
+}
+
+fn parse_holder(s: &str) -> Res<Part> {
+    let (body, case) = match s.rfind('|') {
+        Some(i) => (&s[..i], parse_case(Some(&s[i + 1..]))?),
+        None => (s, Case::Keep),
+    };
+    let (key, format) = match body.find(':') {
+        Some(i) => (&body[..i], Some(&body[i + 1..])),
+        None => (body, None),
+    };
+    let key = match (key, format) {
+        ("name", None) => Key::Name,
+        ("stem", None) => Key::Stem,
+        ("ext", None) => Key::Ext,
+        ("n", None) => Key::Counter(0),
+        ("n", Some(w)) => Key::Counter(w.parse().map_err(|_| invalid(s, "invalid width"))?),
+        ("mtime", f) => {
+            let f = f.unwrap_or(DEFAULT_TIME_FORMAT);
+            if StrftimeItems::new(f).any(|it| it == Item::Error) {
+                return Err(invalid(s, "invalid time format"));
+            }
+            Key::Mtime(f.to_string())
+        }
+        (g, None) if g.chars().all(|it| it.is_ascii_digit()) && !g.is_empty() => {
+            Key::Group(g.parse().unwrap())
+        }
+        _ => return Err(invalid(s, "unknown placeholder")),
+    };
+    Ok(Part::Holder(key, case))
+}
+
+fn parse_template(s: &str) -> Res<Vec<Part>> {
+    let mut parts = Vec::new();
+    let mut text = String::new();
+    let mut chars = s.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '{' if chars.peek() == Some(&'{') => {
+                chars.next();
+                text.push('{');
+            }
+            '}' if chars.peek() == Some(&'}') => {
+                chars.next();
+                text.push('}');
+            }
+            '{' => {
+                let mut holder = String::new();
+                loop {
+                    match chars.next() {
+                        Some('}') => break,
+                        Some(c) => holder.push(c),
+                        None => return Err(invalid(s, "unmatched {")),
+                    }
+                }
+                if !text.is_empty() {
+                    parts.push(Part::Text(std::mem::take(&mut text)));
+                }
+                parts.push(parse_holder(&holder)?);
+            }
+            '}' => return Err(invalid(s, "unmatched }")),
+            c => text.push(c),
+        }
+    }
+    if !text.is_empty() {
+        parts.push(Part::Text(text));
+    }
+    Ok(parts)
+}
+
+// the anchored regex of a glob, every wildcard is a group
+pub fn glob_regex(glob: &str) -> Res<Regex> {
+    let mut re = String::from("^");
+    for c in glob.chars() {
+        match c {
+            '*' => re.push_str("(.*)"),
+            '?' => re.push_str("(.)"),
+            c => re.push_str(&regex::escape(&c.to_string())),
+        }
+    }
+    re.push('$');
+    Regex::new(&re).map_err(|e| invalid(glob, &e.to_string()))
+}
+
+// split `s/REGEX/TEMPLATE/FLAGS`, the `/` in them is escaped as `\/`
+fn split_substitution(s: &str) -> Option<(String, String, String)> {
+    let rest = s.strip_prefix("s/")?;
+    let mut fields = vec![String::new()];
+    let mut chars = rest.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '\\' if chars.peek() == Some(&'/') => {
+                chars.next();
+                fields.last_mut().unwrap().push('/');
+            }
+            '/' => fields.push(String::new()),
+            c => fields.last_mut().unwrap().push(c),
+        }
+    }
+    match fields.as_slice() {
+        [r, t, f] => Some((r.clone(), t.clone(), f.clone())),
+        _ => None,
+    }
+}
+
+fn title(s: &str) -> String {
+    let mut re = String::new();
+    let mut start = true;
+    for c in s.chars() {
+        if start {
+            re.extend(c.to_uppercase());
+        } else {
+            re.extend(c.to_lowercase());
+        }
+        start = !c.is_alphanumeric();
+    }
+    re
+}
+
+impl Pattern {
+    pub fn parse(s: &str) -> Res<Self> {
+        if let Some((r, t, flags)) = split_substitution(s) {
+            if flags.chars().any(|it| it != 'g' && it != 'i') {
+                return Err(invalid(&flags, "unknown flag"));
+            }
+            let regex = RegexBuilder::new(&r)
+                .case_insensitive(flags.contains('i'))
+                .build()
+                .map_err(|e| invalid(&r, &e.to_string()))?;
+            return Ok(Pattern {
+                matcher: Matcher::Regex(regex, flags.contains('g')),
+                template: parse_template(&t)?,
+            });
+        }
+
+        let (matcher, template) = match s.find("=>") {
+            Some(i) => (Matcher::Glob(glob_regex(s[..i].trim())?), s[i + 2..].trim()),
+            None => (Matcher::All, s),
+        };
+        if template.is_empty() {
+            return Err(invalid(s, "empty template"));
+        }
+        Ok(Pattern {
+            matcher,
+            template: parse_template(template)?,
+        })
+    }
+
+    // the new name of the file, None if it does not match, `counter` counts the matched files
+    pub fn apply(&self, subject: &Subject, counter: usize) -> Option<String> {
+        match &self.matcher {
+            Matcher::All => Some(self.expand(subject, counter, None)),
+            Matcher::Glob(r) => {
+                let caps = r.captures(subject.name)?;
+                Some(self.expand(subject, counter, Some(&caps)))
+            }
+            Matcher::Regex(r, global) => {
+                if !r.is_match(subject.name) {
+                    return None;
+                }
+                let limit = if *global { 0 } else { 1 };
+                let re = r.replacen(subject.name, limit, |caps: &Captures| {
+                    self.expand(subject, counter, Some(caps))
+                });
+                Some(re.to_string())
+            }
+        }
+    }
+
+    fn expand(&self, subject: &Subject, counter: usize, caps: Option<&Captures>) -> String {
+        let path = Path::new(subject.name);
+        let stem = path
+            .file_stem()
+            .map_or("".into(), |it| it.to_string_lossy());
+        let ext = path
+            .extension()
+            .map_or("".into(), |it| it.to_string_lossy());
+
+        let mut re = String::new();
+        for p in self.template.iter() {
+            let (key, case) = match p {
+                Part::Text(t) => {
+                    re.push_str(t);
+                    continue;
+                }
+                Part::Holder(k, c) => (k, c),
+            };
+            let value = match key {
+                Key::Name => subject.name.to_string(),
+                Key::Stem => stem.to_string(),
+                Key::Ext => ext.to_string(),
+                Key::Group(g) => caps
+                    .and_then(|it| it.get(*g))
+                    .map_or("".to_string(), |it| it.as_str().to_string()),
+                Key::Counter(w) => format!("{:0width$}", counter, width = *w),
+                Key::Mtime(f) => subject.modified.map_or("".to_string(), |it| {
+                    let dt: DateTime<Local> = it.into();
+                    dt.format(f).to_string()
+                }),
+            };
+            re.push_str(&match case {
+                Case::Keep => value,
+                Case::Upper => value.to_uppercase(),
+                Case::Lower => value.to_lowercase(),
+                Case::Title => title(&value),
+            });
+        }
+        re
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rename(pattern: &str, name: &str, counter: usize) -> Option<String> {
+        let subject = Subject {
+            name,
+            modified: None,
+        };
+        Pattern::parse(pattern).unwrap().apply(&subject, counter)
+    }
+
+    #[test]
+    fn templates() {
+        assert_eq!(
+            rename("{stem|upper}.{ext}", "a b.txt", 1).unwrap(),
+            "A B.txt"
+        );
+        assert_eq!(
+            rename("{n:03}-{name|title}", "my file", 7).unwrap(),
+            "007-My File"
+        );
+        assert_eq!(rename("{{{n}}}", "a", 2).unwrap(), "{2}");
+        assert_eq!(rename("{mtime}{name}", "a", 1).unwrap(), "a");
+        assert!(parse_template("{name}").is_ok());
+    }
+
+    #[test]
+    fn invalid_templates() {
+        for t in &[
+            "a{",
+            "a{name",
+            "a}",
+            "{foo}",
+            "{n:x}",
+            "{name|shout}",
+            "{mtime:%Q}",
+        ] {
+            assert!(
+                matches!(parse_template(t), Err(Error::InvalidPattern(_))),
+                "{}",
+                t
+            );
+        }
+        assert!(Pattern::parse("*.txt => ").is_err());
+        assert!(Pattern::parse("s/a/b/x").is_err());
+    }
+
+    #[test]
+    fn matchers() {
+        assert_eq!(
+            rename("*.jpg => img-{1}.jpg", "x.jpg", 1).unwrap(),
+            "img-x.jpg"
+        );
+        assert_eq!(rename("*.jpg => img-{1}.jpg", "x.png", 1), None);
+        assert_eq!(rename("s/o/0/", "foo", 1).unwrap(), "f0o");
+        assert_eq!(rename("s/O/0/gi", "foo", 1).unwrap(), "f00");
+        assert_eq!(
+            rename(r"s/(\d+)\/x/{1|upper}/", "a12/xb", 1).unwrap(),
+            "a12b"
+        );
+    }
+}
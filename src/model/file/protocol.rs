@@ -1,10 +1,133 @@
-use crate::model::file::InnerFile;
-use async_trait::async_trait;
-use std::io::Result;
+use crate::model::file::local;
+use crate::model::file::path::InnerPath;
+use crate::model::file::{FileInfo, InnerFile, ProtocolInfo};
+use crate::model::result::{Error, Res};
+use lazy_static::lazy_static;
+use std::collections::HashMap;
+use std::convert::TryFrom;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex, RwLock};
 
-#[async_trait]
-pub trait Protocol {
+// A protocol exposes the content of a host file (an archive, a repository...) as a virtual
+// file tree, the path of such a tree is written as `/host/file@name:///path/in/it`.
+pub trait Protocol: Send + Sync {
     fn name(&self) -> &'static str;
+    // whether the given file can be entered through this protocol
     fn support(&self, file: &InnerFile) -> bool;
-    async fn create(&mut self, file: &InnerFile) -> Result<&InnerFile>;
+    // create the file located at `path` inside the protocol instance described by `info`
+    fn create(&self, info: ProtocolInfo, path: &Path) -> Res<InnerFile>;
+    // the path of the parent dir inside the protocol, None if the file is the root of the instance
+    fn parent(&self, info: &FileInfo) -> Option<PathBuf> {
+        info.path.parent().map(|it| it.to_path_buf())
+    }
+    // the info of the host file of an instance, which is a local file by default
+    fn root(&self, host: &Path) -> Res<FileInfo> {
+        local::info(InnerPath::try_from(host)?)
+    }
+}
+
+lazy_static! {
+    static ref PROTOCOLS: RwLock<Vec<Box<dyn Protocol>>> = RwLock::new(Vec::new());
+    // the ids of the protocol instances, an id is never given to another instance
+    static ref INSTANCES: Mutex<HashMap<(String, PathBuf), u64>> = Mutex::new(HashMap::new());
+}
+
+pub fn register<P: Protocol + 'static>(protocol: P) {
+    let mut ps = PROTOCOLS.write().unwrap();
+    if let Some(idx) = ps.iter().position(|it| it.name() == protocol.name()) {
+        ps[idx] = Box::new(protocol);
+    } else {
+        ps.push(Box::new(protocol));
+    }
+}
+
+pub fn make(inner: InnerPath) -> Res<InnerFile> {
+    let pi = match &inner.protocol {
+        Some(v) => v.clone(),
+        None => return local::make(inner),
+    };
+
+    let root = {
+        let ps = PROTOCOLS.read().unwrap();
+        match ps.iter().find(|it| it.name() == pi.protocol) {
+            Some(p) => p.root(&inner.path)?,
+            None => return Err(Error::ProtocolNotSupported(pi.protocol)),
+        }
+    };
+    let info = ProtocolInfo {
+        instance_id: instance_id(&pi.protocol, &root.path),
+        protocol: pi.protocol,
+        root: Box::new(Arc::new(root)),
+    };
+    create(info, &pi.path)
+}
+
+// whether the file can be entered as a dir by a protocol, e.g. an archive
+pub fn supported(file: &InnerFile) -> bool {
+    PROTOCOLS.read().unwrap().iter().any(|it| it.support(file))
+}
+
+// enter a file through the first protocol which supports it, None if no protocol supports it
+pub fn enter(file: &InnerFile) -> Option<Res<InnerFile>> {
+    let name = {
+        let ps = PROTOCOLS.read().unwrap();
+        ps.iter().find(|it| it.support(file)).map(|it| it.name())
+    }?;
+
+    let inner = InnerPath::new_protocol(&file.info().path, name, Path::new("/"));
+    Some(make(inner))
+}
+
+pub fn parent(info: &FileInfo) -> Res<InnerFile> {
+    let pi = match &info.protocol {
+        Some(v) => v,
+        None => return local::parent(info),
+    };
+
+    let parent = {
+        let ps = PROTOCOLS.read().unwrap();
+        match ps.iter().find(|it| it.name() == pi.protocol) {
+            Some(p) => p.parent(info),
+            None => return Err(Error::ProtocolNotSupported(pi.protocol.clone())),
+        }
+    };
+
+    match parent {
+        Some(p) => create(pi.clone(), &p),
+        None => local::parent(pi.root.as_ref()),
+    }
+}
+
+pub fn create(info: ProtocolInfo, path: &Path) -> Res<InnerFile> {
+    let ps = PROTOCOLS.read().unwrap();
+    match ps.iter().find(|it| it.name() == info.protocol) {
+        Some(p) => p.create(info, path),
+        None => Err(Error::ProtocolNotSupported(info.protocol)),
+    }
+}
+
+// create the info of a file inside a protocol instance, the other fields are filled by the caller
+pub fn file_info(info: &ProtocolInfo, path: &Path, is_dir: bool) -> FileInfo {
+    FileInfo {
+        inner: InnerPath::new_protocol(&info.root.path, &info.protocol, path),
+        name: path
+            .file_name()
+            .map(|it| it.to_string_lossy().to_string())
+            .unwrap_or_else(|| info.root.name.clone()),
+        path: path.to_path_buf(),
+        size: 0,
+        mode: 0,
+        modified: None,
+        stat: None,
+        is_dir,
+        link: None,
+        protocol: Some(info.clone()),
+    }
+}
+
+fn instance_id(protocol: &str, root: &Path) -> u64 {
+    let mut ins = INSTANCES.lock().unwrap();
+    let next = ins.len() as u64;
+    *ins.entry((protocol.to_string(), root.to_path_buf()))
+        .or_insert(next)
 }
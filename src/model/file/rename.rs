@@ -0,0 +1,192 @@
+// Rename many files at once. All the renames are checked before any of them is done, the
+// conflicting ones are reported per file, and the rest are ordered so that chains, swaps and
+// cycles of renames go through temporary names instead of overwriting each other.
+use crate::model::file::conflict::{conflict_line, file_name};
+use crate::model::file::pattern::{Pattern, Subject};
+use crate::model::journal::Step;
+use crate::model::result::{Error, Res};
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+
+// the renames from the first path to the second
+pub type Renames = Vec<(PathBuf, PathBuf)>;
+
+// the new path of `from` renamed to `name`, which must be a plain file name
+pub fn target(from: &Path, name: &str) -> Res<PathBuf> {
+    if name.is_empty() || name == "." || name == ".." || name.contains('/') {
+        return Err(Error::InvalidPath(name.to_string()));
+    }
+    Ok(from.with_file_name(name))
+}
+
+// a temporary name in the dir of `path`, which is neither existing nor planned
+fn temp_path(path: &Path, planned: &HashSet<PathBuf>) -> PathBuf {
+    (0..)
+        .map(|i| path.with_file_name(format!(".fff-rename-{}-{}", std::process::id(), i)))
+        .find(|it| !planned.contains(it) && it.symlink_metadata().is_err())
+        .unwrap()
+}
+
+// the renames in the order to be done, and the ones left out for conflicts
+pub fn plan(pairs: Renames) -> (Renames, Vec<(PathBuf, Error)>) {
+    let pairs: Vec<_> = pairs.into_iter().filter(|(f, t)| f != t).collect();
+    let mut counts = HashMap::new();
+    for (_, t) in pairs.iter() {
+        *counts.entry(t.clone()).or_insert(0) += 1;
+    }
+
+    let mut conflicts = Vec::new();
+    let mut valid = Vec::new();
+    for (f, t) in pairs {
+        if counts[&t] > 1 {
+            conflicts.push((f, Error::FileAlreadyExists(t.display().to_string())));
+        } else {
+            valid.push((f, t));
+        }
+    }
+
+    // an existing target is fine only if it is renamed away by another valid rename
+    loop {
+        let sources: HashSet<_> = valid.iter().map(|(f, _)| f.clone()).collect();
+        let (ok, bad): (Vec<_>, Vec<_>) = valid
+            .into_iter()
+            .partition(|(_, t)| sources.contains(t) || t.symlink_metadata().is_err());
+        valid = ok;
+        if bad.is_empty() {
+            break;
+        }
+        for (f, t) in bad {
+            conflicts.push((f, Error::FileAlreadyExists(t.display().to_string())));
+        }
+    }
+
+    let mut planned: HashSet<_> = valid.iter().map(|(_, t)| t.clone()).collect();
+    let mut moves = Vec::new();
+    while !valid.is_empty() {
+        let sources: HashSet<_> = valid.iter().map(|(f, _)| f.clone()).collect();
+        match valid.iter().position(|(_, t)| !sources.contains(t)) {
+            Some(i) => moves.push(valid.remove(i)),
+            // every target is still taken by another rename, the cycle is broken here
+            None => {
+                let (f, t) = valid.remove(0);
+                let temp = temp_path(&f, &planned);
+                planned.insert(temp.clone());
+                moves.push((f, temp.clone()));
+                valid.push((temp, t));
+            }
+        }
+    }
+    (moves, conflicts)
+}
+
+// the renames of the files by the pattern, the lines to preview them, the count of the
+// renamed files and the count of the conflicts, the files are the paths and modified times
+pub fn pattern_plan(
+    pattern: &Pattern,
+    files: &[(PathBuf, Option<SystemTime>)],
+) -> (Renames, Vec<String>, usize, usize) {
+    let mut pairs = Vec::new();
+    let mut conflicts = Vec::new();
+    let mut counter = 0;
+    for (path, modified) in files {
+        let name = file_name(path);
+        let subject = Subject {
+            name: &name,
+            modified: *modified,
+        };
+        if let Some(new) = pattern.apply(&subject, counter + 1) {
+            counter += 1;
+            match target(path, &new) {
+                Ok(t) if &t == path => {}
+                Ok(t) => pairs.push((path.clone(), t)),
+                Err(e) => conflicts.push((path.clone(), e)),
+            }
+        }
+    }
+
+    let renamed = pairs.clone();
+    let (moves, cs) = plan(pairs);
+    conflicts.extend(cs);
+    let mut lines: Vec<_> = renamed
+        .iter()
+        .filter(|(f, _)| conflicts.iter().all(|(c, _)| c != f))
+        .map(|(f, t)| format!("{} -> {}", file_name(f), file_name(t)))
+        .collect();
+    let count = lines.len();
+    lines.extend(conflicts.iter().map(|(f, e)| conflict_line(f, e)));
+    (moves, lines, count, conflicts.len())
+}
+
+// do the planned renames in order, the done ones are kept in `steps`, a failed rename does not
+// stop the rest, and nothing is overwritten even if a cycle is broken by the failure
+pub fn apply(moves: Renames, steps: &mut Vec<Step>) -> Vec<(PathBuf, Error)> {
+    let mut failed = Vec::new();
+    for (from, to) in moves {
+        // something may be created after planned
+        if to.symlink_metadata().is_ok() {
+            failed.push((from, Error::FileAlreadyExists(to.display().to_string())));
+            continue;
+        }
+        match fs::rename(&from, &to) {
+            Ok(_) => steps.push(Step::Move { from, to }),
+            Err(e) => failed.push((from, e.into())),
+        }
+    }
+    failed
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("fff-rename-{}-{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        for f in &["a", "b", "c"] {
+            fs::write(dir.join(f), f).unwrap();
+        }
+        dir
+    }
+
+    #[test]
+    fn chains_and_conflicts() {
+        let d = dir("plan");
+        let p = |n: &str| d.join(n);
+        let pairs = vec![
+            (p("a"), p("b")),
+            (p("b"), p("d")),
+            (p("c"), p("e")),
+            (p("x"), p("e")),
+        ];
+        let (moves, conflicts) = plan(pairs);
+        assert_eq!(moves, vec![(p("b"), p("d")), (p("a"), p("b"))]);
+        let mut failed: Vec<_> = conflicts.into_iter().map(|(f, _)| f).collect();
+        failed.sort();
+        assert_eq!(failed, vec![p("c"), p("x")]);
+
+        let (moves, conflicts) = plan(vec![(p("a"), p("c"))]);
+        assert!(moves.is_empty());
+        assert!(matches!(conflicts[0].1, Error::FileAlreadyExists(_)));
+        fs::remove_dir_all(&d).unwrap();
+    }
+
+    #[test]
+    fn swaps_through_temp_names() {
+        let d = dir("swap");
+        let (a, b) = (d.join("a"), d.join("b"));
+        let (moves, conflicts) = plan(vec![(a.clone(), b.clone()), (b.clone(), a.clone())]);
+        assert!(conflicts.is_empty());
+        assert_eq!(moves.len(), 3);
+
+        let mut steps = Vec::new();
+        assert!(apply(moves, &mut steps).is_empty());
+        assert_eq!(steps.len(), 3);
+        assert_eq!(fs::read_to_string(&a).unwrap(), "b");
+        assert_eq!(fs::read_to_string(&b).unwrap(), "a");
+        assert_eq!(fs::read_dir(&d).unwrap().count(), 3);
+        fs::remove_dir_all(&d).unwrap();
+    }
+}
@@ -0,0 +1,132 @@
+// The unix metadata of the files on disk, the owner and the group are resolved to their names.
+use lazy_static::lazy_static;
+use std::collections::HashMap;
+use std::ffi::{CStr, CString};
+use std::fs::Metadata;
+use std::os::unix::fs::MetadataExt;
+use std::sync::Mutex;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+lazy_static! {
+    static ref USERS: Mutex<HashMap<u32, String>> = Mutex::new(HashMap::new());
+    static ref GROUPS: Mutex<HashMap<u32, String>> = Mutex::new(HashMap::new());
+}
+
+const NAME_BUF_SIZE: usize = 4096;
+
+#[derive(Clone, Default)]
+pub struct Stat {
+    pub owner: String,
+    pub group: String,
+    pub inode: u64,
+    pub nlink: u64,
+    pub accessed: Option<SystemTime>,
+    pub changed: Option<SystemTime>,
+}
+
+impl From<&Metadata> for Stat {
+    fn from(meta: &Metadata) -> Self {
+        Stat {
+            owner: user_name(meta.uid()),
+            group: group_name(meta.gid()),
+            inode: meta.ino(),
+            nlink: meta.nlink(),
+            accessed: meta.accessed().ok(),
+            changed: time(meta.ctime(), meta.ctime_nsec()),
+        }
+    }
+}
+
+fn time(secs: i64, nanos: i64) -> Option<SystemTime> {
+    if secs < 0 {
+        return None;
+    }
+    Some(UNIX_EPOCH + Duration::new(secs as u64, nanos as u32))
+}
+
+fn cached(
+    cache: &Mutex<HashMap<u32, String>>,
+    id: u32,
+    lookup: fn(u32) -> Option<String>,
+) -> String {
+    let mut c = cache.lock().unwrap();
+    c.entry(id)
+        .or_insert_with(|| lookup(id).unwrap_or_else(|| id.to_string()))
+        .clone()
+}
+
+// the name of the user, or the uid if it has no name
+pub fn user_name(uid: u32) -> String {
+    cached(&USERS, uid, |id| unsafe {
+        let mut pwd: libc::passwd = std::mem::zeroed();
+        let mut buf = [0 as libc::c_char; NAME_BUF_SIZE];
+        let mut re = std::ptr::null_mut();
+        libc::getpwuid_r(id, &mut pwd, buf.as_mut_ptr(), buf.len(), &mut re);
+        if re.is_null() {
+            return None;
+        }
+        Some(CStr::from_ptr(pwd.pw_name).to_string_lossy().to_string())
+    })
+}
+
+// the name of the group, or the gid if it has no name
+pub fn group_name(gid: u32) -> String {
+    cached(&GROUPS, gid, |id| unsafe {
+        let mut grp: libc::group = std::mem::zeroed();
+        let mut buf = [0 as libc::c_char; NAME_BUF_SIZE];
+        let mut re = std::ptr::null_mut();
+        libc::getgrgid_r(id, &mut grp, buf.as_mut_ptr(), buf.len(), &mut re);
+        if re.is_null() {
+            return None;
+        }
+        Some(CStr::from_ptr(grp.gr_name).to_string_lossy().to_string())
+    })
+}
+
+// the uid of the user name or number
+pub fn user_id(name: &str) -> Option<u32> {
+    if let Ok(id) = name.parse() {
+        return Some(id);
+    }
+    let name = CString::new(name).ok()?;
+    unsafe {
+        let mut pwd: libc::passwd = std::mem::zeroed();
+        let mut buf = [0 as libc::c_char; NAME_BUF_SIZE];
+        let mut re = std::ptr::null_mut();
+        libc::getpwnam_r(
+            name.as_ptr(),
+            &mut pwd,
+            buf.as_mut_ptr(),
+            buf.len(),
+            &mut re,
+        );
+        if re.is_null() {
+            return None;
+        }
+        Some(pwd.pw_uid)
+    }
+}
+
+// the gid of the group name or number
+pub fn group_id(name: &str) -> Option<u32> {
+    if let Ok(id) = name.parse() {
+        return Some(id);
+    }
+    let name = CString::new(name).ok()?;
+    unsafe {
+        let mut grp: libc::group = std::mem::zeroed();
+        let mut buf = [0 as libc::c_char; NAME_BUF_SIZE];
+        let mut re = std::ptr::null_mut();
+        libc::getgrnam_r(
+            name.as_ptr(),
+            &mut grp,
+            buf.as_mut_ptr(),
+            buf.len(),
+            &mut re,
+        );
+        if re.is_null() {
+            return None;
+        }
+        Some(grp.gr_gid)
+    }
+}
@@ -0,0 +1,659 @@
+// The freedesktop trash, see https://specifications.freedesktop.org/trash-spec/trashspec-latest.html
+// Files in the home trash live in `$XDG_DATA_HOME/Trash/files` with their `.trashinfo` in
+// `$XDG_DATA_HOME/Trash/info`, files on other devices are moved to `$topdir/.Trash/$uid` if the
+// admin has made a sticky `$topdir/.Trash`, or to `$topdir/.Trash-$uid` otherwise.
+//
+// The home trash can be browsed through the `trash` protocol, `$XDG_DATA_HOME/Trash@trash:///`,
+// the trashed items are named by their original path and dated by their deletion date, the root
+// of the home trash lists the items in the trashes of the mount points too.
+use crate::model::context::Context;
+use crate::model::file::archive::Index;
+use crate::model::file::path::InnerPath;
+use crate::model::file::protocol::{self, Protocol};
+use crate::model::file::stat::Stat;
+use crate::model::file::{copy_all, remove_all};
+use crate::model::file::{DirOp, FileInfo, FileOp, InnerFile, LinkInfo, Op, ProtocolInfo};
+use crate::model::result::{option_from_result, Error, Res, Void};
+use crate::model::task::Progress;
+use async_trait::async_trait;
+use chrono::{Local, NaiveDateTime, TimeZone};
+use std::convert::TryFrom;
+use std::ffi::{OsStr, OsString};
+use std::fs::{self, DirBuilder, OpenOptions};
+use std::io::{ErrorKind, Write};
+use std::os::unix::ffi::{OsStrExt, OsStringExt};
+use std::os::unix::fs::{DirBuilderExt, MetadataExt};
+use std::path::{Component, Path, PathBuf};
+use std::time::SystemTime;
+
+const NAME: &str = "trash";
+const INFO_EXT: &str = ".trashinfo";
+const DATE_FORMAT: &str = "%Y-%m-%dT%H:%M:%S";
+// the suffixes tried to name a trashed item uniquely
+const MAX_SUFFIX: usize = 10000;
+const STICKY: u32 = 0o1000;
+
+fn home_trash() -> Res<PathBuf> {
+    // the tests never touch the real trash
+    if cfg!(test) {
+        return Ok(std::env::temp_dir().join(format!("fff-home-trash-{}", std::process::id())));
+    }
+    dirs::data_dir()
+        .map(|it| it.join("Trash"))
+        .ok_or_else(|| Error::PathNotExists("$XDG_DATA_HOME".to_string()))
+}
+
+// the protocol path to browse the home trash, the trash is created if it does not exist
+pub fn trash_path() -> Res<InnerPath> {
+    let home = home_trash()?;
+    fs::create_dir_all(home.join("files"))?;
+    fs::create_dir_all(home.join("info"))?;
+    Ok(InnerPath::new_protocol(&home, NAME, Path::new("/")))
+}
+
+// the top most dir of the mount point containing `path`
+fn top_dir(path: &Path, dev: u64) -> PathBuf {
+    let mut top = path.to_path_buf();
+    for a in path.ancestors().skip(1) {
+        match a.metadata() {
+            Ok(m) if m.dev() == dev => top = a.to_path_buf(),
+            _ => break,
+        }
+    }
+    top
+}
+
+fn top_trash(top: &Path) -> Res<PathBuf> {
+    let uid = unsafe { libc::getuid() };
+    if let Some(shared) = shared_trash(top, uid) {
+        match user_dir(&shared, uid) {
+            Ok(trash) => return Ok(trash),
+            Err(e) => log::error!("trash in {} failed: {:?}", shared.display(), e),
+        }
+    }
+    user_dir(&top.join(format!(".Trash-{}", uid)), uid)
+}
+
+// `$topdir/.Trash/$uid` if `$topdir/.Trash` is made by the admin, a link or a non sticky dir
+// could be made by anyone, so it is not used
+fn shared_trash(top: &Path, uid: u32) -> Option<PathBuf> {
+    let shared = top.join(".Trash");
+    match fs::symlink_metadata(&shared) {
+        Ok(m) if m.is_dir() && m.mode() & STICKY != 0 => Some(shared.join(uid.to_string())),
+        Ok(_) => {
+            log::error!("{} is not a sticky dir", shared.display());
+            None
+        }
+        Err(_) => None,
+    }
+}
+
+// the existing trashes in the top dirs of the mount points listed in `mounts`, which is in the
+// format of `/proc/mounts`
+fn mounted_trashes(mounts: &str) -> Vec<PathBuf> {
+    let uid = unsafe { libc::getuid() };
+    let mut re = Vec::new();
+    for top in mounts.lines().filter_map(|it| it.split_whitespace().nth(1)) {
+        let top = unescape(top);
+        let own = top.join(format!(".Trash-{}", uid));
+        for t in shared_trash(&top, uid).into_iter().chain(Some(own)) {
+            let valid = matches!(fs::symlink_metadata(&t), Ok(m) if m.is_dir() && m.uid() == uid);
+            if valid && !re.contains(&t) {
+                re.push(t);
+            }
+        }
+    }
+    re
+}
+
+// the mount points in `/proc/mounts` have the spaces and the like escaped as `\ooo`
+fn unescape(s: &str) -> PathBuf {
+    let bs = s.as_bytes();
+    let mut re = Vec::with_capacity(bs.len());
+    let mut i = 0;
+    while i < bs.len() {
+        let code = bs
+            .get(i + 1..i + 4)
+            .filter(|_| bs[i] == b'\\')
+            .and_then(|it| u8::from_str_radix(std::str::from_utf8(it).ok()?, 8).ok());
+        match code {
+            Some(c) => {
+                re.push(c);
+                i += 4;
+            }
+            None => {
+                re.push(bs[i]);
+                i += 1;
+            }
+        }
+    }
+    PathBuf::from(OsString::from_vec(re))
+}
+
+// the dir owned by the user only, it is created if missing, a link or a dir of another user is
+// refused
+fn user_dir(dir: &Path, uid: u32) -> Res<PathBuf> {
+    match fs::symlink_metadata(dir) {
+        Ok(m) if m.is_dir() && m.uid() == uid => Ok(dir.to_path_buf()),
+        Ok(_) => Err(Error::InvalidPath(dir.display().to_string())),
+        Err(e) if e.kind() == ErrorKind::NotFound => {
+            DirBuilder::new().mode(0o700).create(dir)?;
+            Ok(dir.to_path_buf())
+        }
+        Err(e) => Err(e.into()),
+    }
+}
+
+// the trash dir for `path`, and the dir the paths in `.trashinfo` are relative to,
+// the home trash is used when the trash of the mount point can not be created
+fn trash_for(path: &Path) -> Res<(PathBuf, Option<PathBuf>)> {
+    let home = home_trash()?;
+    fs::create_dir_all(&home)?;
+
+    let dev = path.symlink_metadata()?.dev();
+    if home.metadata()?.dev() == dev {
+        return Ok((home, None));
+    }
+
+    let top = top_dir(path.parent().unwrap_or(path), dev);
+    match top_trash(&top) {
+        Ok(trash) => Ok((trash, Some(top))),
+        Err(e) => {
+            log::error!("trash in {} failed: {:?}", top.display(), e);
+            Ok((home, None))
+        }
+    }
+}
+
+// percent encode the path as required by the `Path` key of `.trashinfo`
+fn encode(path: &Path) -> String {
+    path.as_os_str()
+        .as_bytes()
+        .iter()
+        .map(|&b| match b {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'/' | b'-' | b'_' | b'.' | b'~' => {
+                (b as char).to_string()
+            }
+            _ => format!("%{:02X}", b),
+        })
+        .collect()
+}
+
+fn decode(s: &str) -> PathBuf {
+    let bs = s.as_bytes();
+    let mut re = Vec::new();
+    let mut i = 0;
+    while i < bs.len() {
+        let hex = bs
+            .get(i + 1..i + 3)
+            .and_then(|it| std::str::from_utf8(it).ok());
+        match (bs[i], hex.and_then(|it| u8::from_str_radix(it, 16).ok())) {
+            (b'%', Some(b)) => {
+                re.push(b);
+                i += 3;
+            }
+            (b, _) => {
+                re.push(b);
+                i += 1;
+            }
+        }
+    }
+    PathBuf::from(OsString::from_vec(re))
+}
+
+// create the `.trashinfo` file with an unique name, the name is returned
+fn write_info(trash: &Path, name: &str, path: &Path) -> Res<String> {
+    let info = trash.join("info");
+    fs::create_dir_all(&info)?;
+    fs::create_dir_all(trash.join("files"))?;
+
+    let content = format!(
+        "[Trash Info]\nPath={}\nDeletionDate={}\n",
+        encode(path),
+        Local::now().format(DATE_FORMAT)
+    );
+    for i in 0..MAX_SUFFIX {
+        let n = if i == 0 {
+            name.to_string()
+        } else {
+            format!("{}.{}", name, i)
+        };
+        if trash.join("files").join(&n).symlink_metadata().is_ok() {
+            continue;
+        }
+
+        let f = OpenOptions::new()
+            .write(true)
+            .create_new(true)
+            .open(info.join(format!("{}{}", n, INFO_EXT)));
+        match f {
+            Ok(mut f) => {
+                f.write_all(content.as_bytes())?;
+                return Ok(n);
+            }
+            Err(e) if e.kind() == ErrorKind::AlreadyExists => continue,
+            Err(e) => return Err(e.into()),
+        }
+    }
+    Err(Error::FileAlreadyExists(
+        trash.join("files").join(name).display().to_string(),
+    ))
+}
+
+// rename `from` to `to`, it is copied then removed if they are not on the same device
+fn move_to(from: &Path, to: &Path) -> Void {
+    match fs::rename(from, to) {
+        Err(e) if e.raw_os_error() == Some(libc::EXDEV) => {
+            if let Err(e) = copy_all(from, to, &Progress::new()) {
+                let _ = remove_all(to);
+                return Err(e);
+            }
+            remove_all(from)
+        }
+        re => Ok(re?),
+    }
+}
+
+// move the file or the dir at `path` to the trash
+pub fn trash(path: &Path) -> Res<PathBuf> {
+    let name = match path.file_name() {
+        Some(v) => v.to_string_lossy().to_string(),
+        None => return Err(Error::InvalidPath(path.display().to_string())),
+    };
+
+    let (trash, top) = trash_for(path)?;
+    let recorded = match &top {
+        Some(t) => path.strip_prefix(t).unwrap_or(path),
+        None => path,
+    };
+    let n = write_info(&trash, &name, recorded)?;
+    let trashed = trash.join("files").join(&n);
+    if let Err(e) = move_to(path, &trashed) {
+        fs::remove_file(trash.join("info").join(format!("{}{}", n, INFO_EXT)))?;
+        return Err(e);
+    }
+    Ok(trashed)
+}
+
+// the content of a `.trashinfo` file
+struct TrashInfo {
+    path: PathBuf,
+    deleted: Option<SystemTime>,
+}
+
+impl TrashInfo {
+    fn info_file(trash: &Path, name: &str) -> PathBuf {
+        trash.join("info").join(format!("{}{}", name, INFO_EXT))
+    }
+
+    fn read(trash: &Path, name: &str) -> Res<Self> {
+        let content = fs::read_to_string(TrashInfo::info_file(trash, name))?;
+        let mut path = None;
+        let mut deleted = None;
+        for line in content.lines() {
+            if let Some(v) = line.strip_prefix("Path=") {
+                // relative paths are used by the trash in the top dir of a mount point
+                let p = decode(v.trim());
+                path = Some(match top_of(trash) {
+                    Some(top) if p.is_relative() => top.join(p),
+                    _ => p,
+                });
+            } else if let Some(v) = line.strip_prefix("DeletionDate=") {
+                deleted = NaiveDateTime::parse_from_str(v.trim(), DATE_FORMAT)
+                    .ok()
+                    .and_then(|it| Local.from_local_datetime(&it).single())
+                    .map(|it| it.into());
+            }
+        }
+
+        match path {
+            Some(path) => Ok(TrashInfo { path, deleted }),
+            None => Err(Error::InvalidPath(name.to_string())),
+        }
+    }
+}
+
+// the top dir of a trash in a mount point, which is `$topdir/.Trash/$uid` or `$topdir/.Trash-$uid`
+fn top_of(trash: &Path) -> Option<&Path> {
+    let parent = trash.parent()?;
+    if parent.file_name() == Some(OsStr::new(".Trash")) {
+        parent.parent()
+    } else {
+        Some(parent)
+    }
+}
+
+// the name of the top level trashed item containing the file, None for the root of the trash
+fn trashed_name(info: &FileInfo) -> Option<String> {
+    info.path.components().find_map(|it| match it {
+        Component::Normal(n) => Some(n.to_string_lossy().to_string()),
+        _ => None,
+    })
+}
+
+fn is_trashed_item(info: &FileInfo) -> bool {
+    is_trash(info) && info.path.parent() == Some(Path::new("/"))
+}
+
+// the original path of a top level trashed item browsed through the trash protocol
+pub fn original_path(info: &FileInfo) -> Res<PathBuf> {
+    match (is_trashed_item(info), trashed_name(info)) {
+        (true, Some(n)) => Ok(TrashInfo::read(&protocol_root(info), &n)?.path),
+        _ => Err(Error::InvalidPath(info.inner.to_string())),
+    }
+}
+
+// the path of a top level trashed item in the `files` dir of its trash
+pub fn trashed_path(info: &FileInfo) -> Res<PathBuf> {
+    match (is_trashed_item(info), trashed_name(info)) {
+        (true, Some(n)) => Ok(protocol_root(info).join("files").join(n)),
+        _ => Err(Error::InvalidPath(info.inner.to_string())),
+    }
+}
+
+// move a top level trashed item back to `to`, usually its original path, which must not exist
+pub fn restore(info: &FileInfo, to: &Path) -> Void {
+    restore_trashed(&trashed_path(info)?, to)
+}
+
+// move the trashed item at `trashed`, as returned by `trash`, back to `to` which must not exist
+pub fn restore_trashed(trashed: &Path, to: &Path) -> Void {
+    let (trash, name) = match (
+        trashed.parent().and_then(|it| it.parent()),
+        trashed.file_name(),
+    ) {
+        (Some(t), Some(n)) => (t, n.to_string_lossy().to_string()),
+        _ => return Err(Error::InvalidPath(trashed.display().to_string())),
+    };
+    if to.symlink_metadata().is_ok() {
+        return Err(Error::FileAlreadyExists(to.display().to_string()));
+    }
+    if let Some(p) = to.parent() {
+        fs::create_dir_all(p)?;
+    }
+
+    move_to(trashed, to)?;
+    fs::remove_file(TrashInfo::info_file(trash, &name))?;
+    Ok(())
+}
+
+// delete everything in the home trash
+pub fn empty() -> Void {
+    let home = home_trash()?;
+    for d in &["files", "info"] {
+        let p = home.join(d);
+        if p.exists() {
+            fs::remove_dir_all(&p)?;
+        }
+        fs::create_dir_all(&p)?;
+    }
+    Ok(())
+}
+
+// whether the file is browsed through the trash protocol
+pub fn is_trash(info: &FileInfo) -> bool {
+    matches!(&info.protocol, Some(p) if p.protocol == NAME)
+}
+
+fn protocol_root(info: &FileInfo) -> PathBuf {
+    info.protocol.as_ref().unwrap().root.path.clone()
+}
+
+pub struct TrashProtocol;
+
+impl Protocol for TrashProtocol {
+    fn name(&self) -> &'static str {
+        NAME
+    }
+
+    // the trash is opened by an action
+    fn support(&self, _: &InnerFile) -> bool {
+        false
+    }
+
+    fn create(&self, info: ProtocolInfo, path: &Path) -> Res<InnerFile> {
+        let p = Index::normalize(&path.display().to_string());
+        let real = match p.strip_prefix("/") {
+            Ok(r) if r.as_os_str().is_empty() => info.root.path.join("files"),
+            Ok(r) => info.root.path.join("files").join(r),
+            Err(_) => return Err(Error::InvalidPath(p.display().to_string())),
+        };
+        let meta = match real.symlink_metadata() {
+            Ok(m) => m,
+            Err(_) => {
+                return Err(Error::PathNotExists(
+                    InnerPath::new_protocol(&info.root.path, NAME, &p).to_string(),
+                ))
+            }
+        };
+
+        let mut fi = protocol::file_info(&info, &p, meta.is_dir());
+        fi.size = meta.len();
+        fi.mode = meta.mode();
+        fi.stat = Some(Stat::from(&meta));
+        fi.modified = option_from_result(meta.modified());
+        if meta.file_type().is_symlink() {
+            fi.link = option_from_result(fs::read_link(&real)).map(|t| LinkInfo {
+                broken: !real.exists(),
+                target: t.display().to_string(),
+            });
+        }
+        if p.parent() == Some(Path::new("/")) {
+            if let Ok(ti) = TrashInfo::read(&info.root.path, &fi.name) {
+                fi.name = ti.path.display().to_string();
+                fi.modified = ti.deleted.or(fi.modified);
+            }
+        }
+
+        let file = TrashFile { info: fi, real };
+        Ok(if file.info.is_dir {
+            InnerFile::Dir(Box::new(file))
+        } else {
+            InnerFile::File(Box::new(file))
+        })
+    }
+}
+
+// a file in the trash, `real` is its location in the `files` dir of the trash
+pub struct TrashFile {
+    info: FileInfo,
+    real: PathBuf,
+}
+
+impl TrashFile {
+    // remove the file from the trash, the `.trashinfo` is removed with the top level items
+    fn purge(&self) -> Void {
+        if self.info.is_dir {
+            fs::remove_dir_all(&self.real)?;
+        } else {
+            fs::remove_file(&self.real)?;
+        }
+        if let (true, Some(n)) = (is_trashed_item(&self.info), trashed_name(&self.info)) {
+            fs::remove_file(TrashInfo::info_file(&protocol_root(&self.info), &n))?;
+        }
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl Op for TrashFile {
+    fn get(&self) -> &FileInfo {
+        &self.info
+    }
+    async fn parent(&self, _: &Context) -> Res<InnerFile> {
+        protocol::parent(&self.info)
+    }
+    async fn rename(&self, _: &Context) -> Void {
+        Err(Error::ReadOnly(self.info.inner.to_string()))
+    }
+    // files can not be trashed again, both deletes purge them
+    async fn delete(&self, _: &Context) -> Void {
+        self.purge()
+    }
+    async fn delete_permanently(&self, _: &Context) -> Void {
+        self.purge()
+    }
+    async fn open(&self, _: &Context) -> Void {
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl FileOp for TrashFile {
+    async fn view(&self, ctx: &Context) -> Void {
+        ctx.view(std::slice::from_ref(&self.real)).await?;
+        Ok(())
+    }
+    async fn edit(&self, _: &Context) -> Void {
+        Err(Error::ReadOnly(self.info.inner.to_string()))
+    }
+}
+
+#[async_trait]
+impl DirOp for TrashFile {
+    async fn list(&self, _: &Context) -> Res<Vec<InnerFile>> {
+        let pi = self.info.protocol.as_ref().unwrap();
+        let mut re = list_items(pi, &self.info.path, &self.real)?;
+
+        // the root of the home trash shows the items in the trashes of the mount points too,
+        // they are browsed through their own trash
+        if self.info.path == Path::new("/") && pi.root.path == home_trash()? {
+            let mounts = if cfg!(test) {
+                String::new()
+            } else {
+                fs::read_to_string("/proc/mounts").unwrap_or_default()
+            };
+            for t in mounted_trashes(&mounts) {
+                let root = InnerFile::try_from(InnerPath::new_protocol(&t, NAME, Path::new("/")));
+                let items = root.and_then(|it| {
+                    let pi = it.info().protocol.as_ref().unwrap();
+                    list_items(pi, Path::new("/"), &t.join("files"))
+                });
+                match items {
+                    Ok(fs) => re.extend(fs),
+                    Err(e) => log::error!("list trash {} failed: {:?}", t.display(), e),
+                }
+            }
+        }
+        Ok(re)
+    }
+
+    async fn new_file(&self, _: &Context) -> Void {
+        Err(Error::ReadOnly(self.info.inner.to_string()))
+    }
+
+    async fn new_dir(&self, _: &Context) -> Void {
+        Err(Error::ReadOnly(self.info.inner.to_string()))
+    }
+
+    async fn goto(&self, _: &Context, child_path: &str) -> Res<InnerFile> {
+        let pi = self.info.protocol.as_ref().unwrap();
+        protocol::create(pi.clone(), &self.info.path.join(child_path))
+    }
+
+    async fn shell(&self, _: &Context, _: &[(String, String)]) -> Void {
+        Ok(())
+    }
+}
+
+// the files in the dir `real` of a trash, `path` is the dir inside the trash
+fn list_items(pi: &ProtocolInfo, path: &Path, real: &Path) -> Res<Vec<InnerFile>> {
+    let mut re = Vec::new();
+    for d in fs::read_dir(real)? {
+        let p = path.join(d?.file_name());
+        // items without `.trashinfo` are left by a failed trashing, they are still listed
+        if let Ok(f) = protocol::create(pi.clone(), &p) {
+            re.push(f);
+        }
+    }
+    Ok(re)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::os::unix::fs::PermissionsExt;
+
+    #[test]
+    fn encode_paths() {
+        assert_eq!(encode(Path::new("/a b/c.txt")), "/a%20b/c.txt");
+        assert_eq!(encode(Path::new("/é")), "/%C3%A9");
+        let raw = PathBuf::from(OsString::from_vec(b"/a\xff".to_vec()));
+        assert_eq!(encode(&raw), "/a%FF");
+        assert_eq!(decode(&encode(&raw)), raw);
+        assert_eq!(decode("/a%20b/%C3%A9"), Path::new("/a b/é"));
+    }
+
+    #[test]
+    fn unique_info_names() {
+        let dir = std::env::temp_dir().join(format!("fff-trash-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        assert_eq!(write_info(&dir, "f", Path::new("/f")).unwrap(), "f");
+        assert_eq!(write_info(&dir, "f", Path::new("/f")).unwrap(), "f.1");
+        fs::write(dir.join("files/f.2"), "").unwrap();
+        assert_eq!(write_info(&dir, "f", Path::new("/f")).unwrap(), "f.3");
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn top_trash_dirs() {
+        let top = std::env::temp_dir().join(format!("fff-trash-top-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&top);
+        fs::create_dir(&top).unwrap();
+        let uid = unsafe { libc::getuid() };
+        let own = top.join(format!(".Trash-{}", uid));
+
+        // without a sticky `.Trash` the own trash is used
+        fs::create_dir(top.join(".Trash")).unwrap();
+        assert_eq!(top_trash(&top).unwrap(), own);
+        let m = fs::symlink_metadata(&own).unwrap();
+        assert_eq!(m.mode() & 0o777, 0o700);
+
+        fs::set_permissions(top.join(".Trash"), fs::Permissions::from_mode(0o1777)).unwrap();
+        let shared = top.join(".Trash").join(uid.to_string());
+        assert_eq!(top_trash(&top).unwrap(), shared);
+        assert!(shared.is_dir());
+
+        // links are never followed
+        fs::remove_dir_all(top.join(".Trash")).unwrap();
+        fs::create_dir(top.join("elsewhere")).unwrap();
+        std::os::unix::fs::symlink(top.join("elsewhere"), top.join(".Trash")).unwrap();
+        assert_eq!(top_trash(&top).unwrap(), own);
+        fs::remove_dir(&own).unwrap();
+        std::os::unix::fs::symlink(top.join("elsewhere"), &own).unwrap();
+        assert!(top_trash(&top).is_err());
+        fs::remove_dir_all(&top).unwrap();
+    }
+
+    #[test]
+    fn trashes_of_mounts() {
+        let top = std::env::temp_dir().join(format!("fff-trash mounts-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&top);
+        let uid = unsafe { libc::getuid() };
+        let own = top.join(format!("b/.Trash-{}", uid));
+        let shared = top.join(format!("a/.Trash/{}", uid));
+        fs::create_dir_all(&own).unwrap();
+        fs::create_dir_all(&shared).unwrap();
+        fs::set_permissions(top.join("a/.Trash"), fs::Permissions::from_mode(0o1777)).unwrap();
+
+        let escaped = top.display().to_string().replace(' ', "\\040");
+        assert_eq!(unescape(&escaped), top);
+        let mounts: String = ["a", "b", "c", "b"]
+            .iter()
+            .map(|it| format!("/dev/sda1 {}/{} ext4 rw 0 0\n", escaped, it))
+            .collect();
+        assert_eq!(mounted_trashes(&mounts), vec![shared, own]);
+        fs::remove_dir_all(&top).unwrap();
+    }
+
+    #[test]
+    fn relative_info_paths() {
+        let top = std::env::temp_dir().join(format!("fff-trash-rel-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&top);
+        for trash in &[top.join(".Trash/1000"), top.join(".Trash-1000")] {
+            let n = write_info(trash, "f", Path::new("a/f")).unwrap();
+            assert_eq!(TrashInfo::read(trash, &n).unwrap().path, top.join("a/f"));
+        }
+        fs::remove_dir_all(&top).unwrap();
+    }
+}
@@ -0,0 +1,525 @@
+// A persistent journal of the file operations, every entry holds the steps of one operation,
+// which are replayed in reverse to undo it and replayed again to redo it.
+//
+// The journal is kept in `~/.config/fff/journal`. Every step remembers the modified time and
+// the size of the file it produced, an entry is refused to replay if its files are changed since.
+use crate::model::file::conflict::error_desc;
+use crate::model::file::{copy_all, link, measure, move_all, remove_all, trash};
+use crate::model::result::{Error, Res, Void};
+use crate::model::task::Progress;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::UNIX_EPOCH;
+use toml::value::{Array, Table};
+use toml::Value;
+
+const MAX_ENTRIES: usize = 100;
+
+pub enum Step {
+    Move { from: PathBuf, to: PathBuf },
+    Copy { from: PathBuf, to: PathBuf },
+    Create { path: PathBuf, dir: bool },
+    Trash { from: PathBuf, trashed: PathBuf },
+    Symlink { target: PathBuf, path: PathBuf },
+    HardLink { from: PathBuf, to: PathBuf },
+}
+
+// fails if there is something at `path`, so nothing is overwritten by replaying
+fn vacant(path: &Path) -> Void {
+    if path.symlink_metadata().is_ok() {
+        return Err(Error::FileAlreadyExists(path.display().to_string()));
+    }
+    Ok(())
+}
+
+fn move_back(from: &Path, to: &Path, p: &Progress) -> Void {
+    vacant(to)?;
+    let (bytes, items) = measure(from)?;
+    p.add_total(bytes, items);
+    move_all(from, to, p)
+}
+
+impl Step {
+    // where the file is after the step is done
+    fn done_path(&self) -> &Path {
+        match self {
+            Step::Move { to, .. } | Step::Copy { to, .. } | Step::HardLink { to, .. } => to,
+            Step::Create { path, .. } | Step::Symlink { path, .. } => path,
+            Step::Trash { trashed, .. } => trashed,
+        }
+    }
+
+    // where the file is after the step is undone, None if it is gone
+    fn undone_path(&self) -> Option<&Path> {
+        match self {
+            Step::Move { from, .. } | Step::Trash { from, .. } => Some(from),
+            Step::Copy { .. } | Step::Create { .. } => None,
+            Step::Symlink { .. } | Step::HardLink { .. } => None,
+        }
+    }
+
+    fn undo(&mut self, p: &Progress) -> Void {
+        match self {
+            Step::Move { from, to } => move_back(to, from, p),
+            // files may be added into the copied or created dir since, so it goes to the trash
+            Step::Copy { to: path, .. } | Step::Create { path, .. } => {
+                p.add_total(0, 1);
+                trash::trash(path)?;
+                p.advance(0, 1);
+                Ok(())
+            }
+            Step::Symlink { path, .. } | Step::HardLink { to: path, .. } => {
+                p.add_total(0, 1);
+                remove_all(path)?;
+                p.advance(0, 1);
+                Ok(())
+            }
+            Step::Trash { from, trashed } => {
+                p.add_total(0, 1);
+                trash::restore_trashed(trashed, from)?;
+                p.advance(0, 1);
+                Ok(())
+            }
+        }
+    }
+
+    fn redo(&mut self, p: &Progress) -> Void {
+        match self {
+            Step::Move { from, to } => move_back(from, to, p),
+            Step::Copy { from, to } => {
+                vacant(to)?;
+                let (bytes, items) = measure(from)?;
+                p.add_total(bytes, items);
+                copy_all(from, to, p)
+            }
+            Step::Create { path, dir } => {
+                vacant(path)?;
+                p.add_total(0, 1);
+                if *dir {
+                    fs::create_dir(path)?;
+                } else {
+                    fs::File::create(path)?;
+                }
+                p.advance(0, 1);
+                Ok(())
+            }
+            Step::Trash { from, trashed } => {
+                p.add_total(0, 1);
+                *trashed = trash::trash(from)?;
+                p.advance(0, 1);
+                Ok(())
+            }
+            Step::Symlink { target, path } => {
+                vacant(path)?;
+                p.add_total(0, 1);
+                link(target, path, false)?;
+                p.advance(0, 1);
+                Ok(())
+            }
+            Step::HardLink { from, to } => {
+                vacant(to)?;
+                p.add_total(0, 1);
+                link(from, to, true)?;
+                p.advance(0, 1);
+                Ok(())
+            }
+        }
+    }
+
+    fn to_value(&self) -> Table {
+        let mut t = Table::new();
+        let mut put = |k: &str, v: &Path| {
+            t.insert(k.to_string(), Value::String(v.display().to_string()));
+        };
+        let kind = match self {
+            Step::Move { from, to } => {
+                put("from", from);
+                put("to", to);
+                "move"
+            }
+            Step::Copy { from, to } => {
+                put("from", from);
+                put("to", to);
+                "copy"
+            }
+            Step::Create { path, dir } => {
+                put("path", path);
+                t.insert("dir".to_string(), Value::Boolean(*dir));
+                "create"
+            }
+            Step::Trash { from, trashed } => {
+                put("from", from);
+                put("trashed", trashed);
+                "trash"
+            }
+            Step::Symlink { target, path } => {
+                put("target", target);
+                put("path", path);
+                "symlink"
+            }
+            Step::HardLink { from, to } => {
+                put("from", from);
+                put("to", to);
+                "hardlink"
+            }
+        };
+        t.insert("kind".to_string(), Value::String(kind.to_string()));
+        t
+    }
+
+    fn from_value(t: &Table) -> Option<Self> {
+        let path = |k: &str| t.get(k).and_then(|it| it.as_str()).map(PathBuf::from);
+        Some(match t.get("kind")?.as_str()? {
+            "move" => Step::Move {
+                from: path("from")?,
+                to: path("to")?,
+            },
+            "copy" => Step::Copy {
+                from: path("from")?,
+                to: path("to")?,
+            },
+            "create" => Step::Create {
+                path: path("path")?,
+                dir: t.get("dir")?.as_bool()?,
+            },
+            "trash" => Step::Trash {
+                from: path("from")?,
+                trashed: path("trashed")?,
+            },
+            "symlink" => Step::Symlink {
+                target: path("target")?,
+                path: path("path")?,
+            },
+            "hardlink" => Step::HardLink {
+                from: path("from")?,
+                to: path("to")?,
+            },
+            _ => return None,
+        })
+    }
+}
+
+// the modified time in nanoseconds and the size of the file at `path`
+fn stamp(path: &Path) -> Option<(i64, i64)> {
+    let meta = path.symlink_metadata().ok()?;
+    let mtime = meta.modified().ok()?.duration_since(UNIX_EPOCH).ok()?;
+    Some((mtime.as_nanos() as i64, meta.len() as i64))
+}
+
+struct Record {
+    step: Step,
+    done: bool,
+    stamp: Option<(i64, i64)>,
+}
+
+impl Record {
+    fn current_path(&self) -> Option<&Path> {
+        if self.done {
+            Some(self.step.done_path())
+        } else {
+            self.step.undone_path()
+        }
+    }
+
+    fn update_stamp(&mut self) {
+        self.stamp = self.current_path().and_then(stamp);
+    }
+
+    fn check(&self) -> Void {
+        match self.current_path() {
+            Some(p) if stamp(p) != self.stamp => Err(Error::FileChanged(p.display().to_string())),
+            _ => Ok(()),
+        }
+    }
+}
+
+pub struct Entry {
+    pub desc: String,
+    records: Vec<Record>,
+}
+
+impl Entry {
+    fn check(&self) -> Void {
+        self.records.iter().try_for_each(Record::check)
+    }
+
+    // undo the done steps in reverse, or redo the undone steps, it stops at the first failure
+    // and the replayed steps are kept, so it can be continued later
+    pub fn replay(&mut self, redo: bool, p: &Progress) -> Void {
+        let re = self.replay_steps(redo, p);
+        // a later step may change the file of an earlier one, e.g. a temporary name
+        self.records.iter_mut().for_each(Record::update_stamp);
+        re
+    }
+
+    // replay in the blocking pool, the entry is given back to be put back to the journal
+    pub async fn replay_blocking(mut self, redo: bool, p: Arc<Progress>) -> Res<(Entry, Void)> {
+        Ok(tokio::task::spawn_blocking(move || {
+            let re = self.replay(redo, &p);
+            (self, re)
+        })
+        .await?)
+    }
+
+    fn replay_steps(&mut self, redo: bool, p: &Progress) -> Void {
+        let records: Vec<_> = if redo {
+            self.records.iter_mut().filter(|it| !it.done).collect()
+        } else {
+            self.records.iter_mut().rev().filter(|it| it.done).collect()
+        };
+        for r in records {
+            p.check()?;
+            if redo {
+                r.step.redo(p)?;
+            } else {
+                r.step.undo(p)?;
+            }
+            r.done = redo;
+        }
+        Ok(())
+    }
+
+    fn is_done(&self) -> bool {
+        self.records.iter().all(|it| it.done)
+    }
+
+    fn to_value(&self) -> Value {
+        let mut t = Table::new();
+        t.insert("desc".to_string(), Value::String(self.desc.clone()));
+        let records = self
+            .records
+            .iter()
+            .map(|r| {
+                let mut v = r.step.to_value();
+                v.insert("done".to_string(), Value::Boolean(r.done));
+                if let Some((m, l)) = r.stamp {
+                    let s = vec![Value::Integer(m), Value::Integer(l)];
+                    v.insert("stamp".to_string(), Value::Array(s));
+                }
+                Value::Table(v)
+            })
+            .collect();
+        t.insert("steps".to_string(), Value::Array(records));
+        Value::Table(t)
+    }
+
+    fn from_value(v: &Value) -> Option<Self> {
+        let mut records = Vec::new();
+        for s in v.get("steps")?.as_array()? {
+            let t = s.as_table()?;
+            let stamp = match t.get("stamp").and_then(|it| it.as_array()) {
+                Some(a) if a.len() == 2 => Some((a[0].as_integer()?, a[1].as_integer()?)),
+                _ => None,
+            };
+            records.push(Record {
+                step: Step::from_value(t)?,
+                done: t.get("done")?.as_bool()?,
+                stamp,
+            });
+        }
+        Some(Entry {
+            desc: v.get("desc")?.as_str()?.to_string(),
+            records,
+        })
+    }
+}
+
+// why the last entry can not be taken to undo or redo
+pub fn refusal(redo: bool, e: &Error) -> String {
+    let action = if redo { "redo" } else { "undo" };
+    match e {
+        Error::FileChanged(p) => format!("Can not {}, {} is changed", action, p),
+        e => format!("Can not {}, {}", action, error_desc(e)),
+    }
+}
+
+pub struct Journal {
+    path: PathBuf,
+    undo: Vec<Entry>,
+    redo: Vec<Entry>,
+}
+
+fn read_entries(value: &Value, key: &str) -> Vec<Entry> {
+    value
+        .get(key)
+        .and_then(|it| it.as_array())
+        .map_or_else(Vec::new, |it| {
+            it.iter().filter_map(Entry::from_value).collect()
+        })
+}
+
+impl Journal {
+    pub fn new(home: &Path) -> Self {
+        let path = home.join(".config/fff/journal");
+        let value = fs::read_to_string(&path)
+            .ok()
+            .and_then(|it| it.parse::<Value>().ok());
+        let (undo, redo) = match value {
+            Some(v) => (read_entries(&v, "undo"), read_entries(&v, "redo")),
+            None => (Vec::new(), Vec::new()),
+        };
+        Journal { path, undo, redo }
+    }
+
+    // record the done steps of an operation, the redo history is dropped
+    pub fn record(&mut self, desc: String, steps: Vec<Step>) {
+        if steps.is_empty() {
+            return;
+        }
+        let records = steps
+            .into_iter()
+            .map(|step| {
+                let mut r = Record {
+                    step,
+                    done: true,
+                    stamp: None,
+                };
+                r.update_stamp();
+                r
+            })
+            .collect();
+        self.undo.push(Entry { desc, records });
+        if self.undo.len() > MAX_ENTRIES {
+            self.undo.remove(0);
+        }
+        self.redo.clear();
+        self.save();
+    }
+
+    // take the last entry to undo or redo, it is refused if its files are changed since
+    pub fn take(&mut self, redo: bool) -> Res<Option<Entry>> {
+        let entries = if redo { &mut self.redo } else { &mut self.undo };
+        match entries.last() {
+            Some(e) => e.check()?,
+            None => return Ok(None),
+        }
+        // it is saved after put back, so it is not lost if fff exits while replaying
+        Ok(entries.pop())
+    }
+
+    // put back the replayed entry, it goes back where it was taken if it is not fully replayed
+    pub fn put_back(&mut self, entry: Entry, redo: bool) {
+        if entry.is_done() || (!redo && entry.records.iter().any(|it| it.done)) {
+            self.undo.push(entry);
+        } else {
+            self.redo.push(entry);
+        }
+        self.save();
+    }
+
+    fn save(&self) {
+        let entries =
+            |es: &[Entry]| Value::Array(es.iter().map(Entry::to_value).collect::<Array>());
+        let mut t = Table::new();
+        t.insert("undo".to_string(), entries(&self.undo));
+        t.insert("redo".to_string(), entries(&self.redo));
+
+        let content = match toml::to_string(&Value::Table(t)) {
+            Ok(v) => v,
+            Err(e) => {
+                log::error!("serialize journal failed: {:?}", e);
+                return;
+            }
+        };
+        let re = fs::create_dir_all(self.path.parent().unwrap())
+            .and_then(|_| fs::write(&self.path, content));
+        if let Err(e) = re {
+            log::error!("write journal {} failed: {:?}", self.path.display(), e);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn home(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("fff-journal-{}-{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    #[test]
+    fn round_trip() {
+        let home = home("round");
+        let (a, b) = (home.join("a"), home.join("b"));
+        fs::write(&b, "b").unwrap();
+        let create = Step::Create { path: a, dir: true };
+        let copy = Step::Copy {
+            from: home.join("c"),
+            to: b,
+        };
+        let mut j = Journal::new(&home);
+        j.record("Create a".to_string(), vec![create]);
+        j.record("Copy".to_string(), vec![copy]);
+
+        let read = Journal::new(&home);
+        assert_eq!(read.undo.len(), 2);
+        assert!(read.redo.is_empty());
+        for (r, w) in read.undo.iter().zip(j.undo.iter()) {
+            assert_eq!(r.to_value(), w.to_value());
+        }
+        assert!(read.undo[0].records[0].stamp.is_none());
+        assert!(read.undo[1].records[0].stamp.is_some());
+        fs::remove_dir_all(&home).unwrap();
+    }
+
+    #[test]
+    fn changed_files_are_refused() {
+        let home = home("stamp");
+        let f = home.join("f");
+        fs::write(&f, "f").unwrap();
+        let mut j = Journal::new(&home);
+        let step = Step::Create {
+            path: f.clone(),
+            dir: false,
+        };
+        j.record("Create f".to_string(), vec![step]);
+
+        fs::write(&f, "changed").unwrap();
+        assert!(matches!(j.take(false), Err(Error::FileChanged(_))));
+        j.undo[0].records.iter_mut().for_each(Record::update_stamp);
+
+        let mut e = j.take(false).unwrap().unwrap();
+        e.replay(false, &Progress::new()).unwrap();
+        j.put_back(e, false);
+        assert!(!f.exists());
+        assert_eq!((j.undo.len(), j.redo.len()), (0, 1));
+
+        let mut e = j.take(true).unwrap().unwrap();
+        e.replay(true, &Progress::new()).unwrap();
+        j.put_back(e, true);
+        assert!(f.exists());
+        assert_eq!((j.undo.len(), j.redo.len()), (1, 0));
+        fs::remove_dir_all(&home).unwrap();
+    }
+
+    #[test]
+    fn undone_dirs_go_to_the_trash() {
+        let home = home("trash");
+        let d = home.join("journal-undone-dir");
+        fs::create_dir_all(d.join("e")).unwrap();
+        let mut j = Journal::new(&home);
+        let step = Step::Create {
+            path: d.clone(),
+            dir: true,
+        };
+        j.record("Create d".to_string(), vec![step]);
+
+        // a file added deeper does not change the stamp of the dir
+        fs::write(d.join("e/f"), "f").unwrap();
+        let mut e = j.take(false).unwrap().unwrap();
+        e.replay(false, &Progress::new()).unwrap();
+        assert!(!d.exists());
+
+        let trashed = trash::trash_path()
+            .unwrap()
+            .path
+            .join("files/journal-undone-dir");
+        assert_eq!(fs::read_to_string(trashed.join("e/f")).unwrap(), "f");
+        fs::remove_dir_all(&trashed).unwrap();
+        fs::remove_dir_all(&home).unwrap();
+    }
+}
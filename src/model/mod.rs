@@ -1,4 +1,6 @@
 mod context;
 pub mod file;
+pub mod journal;
 pub mod result;
 pub mod state;
+pub mod task;
@@ -12,9 +12,17 @@ pub enum Error {
     ProtocolNotSupported(String),
 
     FileAlreadyExists(String),
+    FileChanged(String),
+    ReadOnly(String),
+    InvalidArchive(String),
+    CommandFailed(String),
+    SpecialFile(String),
+    Cancelled,
 
     InvalidEnumValue(String),
     InvalidFilter(String),
+    InvalidPattern(String),
+    InvalidAttribute(String),
     DirIsRequired(String),
     SendError(EventBody),
     JoinError(JoinError),
@@ -49,6 +57,15 @@ impl From<crossbeam_channel::SendError<EventBody>> for Error {
     }
 }
 
+impl From<zip::result::ZipError> for Error {
+    fn from(e: zip::result::ZipError) -> Self {
+        match e {
+            zip::result::ZipError::Io(e) => Self::Io(e.kind()),
+            e => Self::InvalidArchive(e.to_string()),
+        }
+    }
+}
+
 impl From<JoinError> for Error {
     fn from(e: JoinError) -> Self {
         Self::JoinError(e)
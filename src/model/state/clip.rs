@@ -0,0 +1,34 @@
+use crate::model::state::list::FileVec;
+
+// files collected from any dir of any group, to be pasted or moved to another dir
+pub struct Clip {
+    files: FileVec,
+}
+
+impl Clip {
+    pub fn new() -> Self {
+        Clip { files: Vec::new() }
+    }
+
+    // append the files which are not in the clip yet, the count of the clip is returned
+    pub fn append(&mut self, files: FileVec) -> usize {
+        files.into_iter().for_each(|f| {
+            if !self.files.iter().any(|it| it.path_str() == f.path_str()) {
+                self.files.push(f);
+            }
+        });
+        self.files.len()
+    }
+
+    pub fn clear(&mut self) {
+        self.files.clear();
+    }
+
+    pub fn files(&self) -> &FileVec {
+        &self.files
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.files.is_empty()
+    }
+}
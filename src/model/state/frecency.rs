@@ -0,0 +1,232 @@
+// The dirs entered, ranked by how often and how lately they are entered, kept in
+// `~/.config/fff/frecency` as a `rank|time|path` line per dir. The ranks are aged as zoxide does,
+// so the dirs no more used are dropped after a while.
+//
+// The file is written at most once a minute while dirs are entered, and when fff exits.
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+// the ranks are aged once their total is over it
+const MAX_AGE: f64 = 10000.0;
+const AGING: f64 = 0.9;
+const HOUR: u64 = 60 * 60;
+const DAY: u64 = 24 * HOUR;
+const WEEK: u64 = 7 * DAY;
+const SAVE_INTERVAL: Duration = Duration::from_secs(60);
+
+#[derive(Clone)]
+struct Entry {
+    path: String,
+    rank: f64,
+    time: u64,
+}
+
+impl Entry {
+    fn parse(line: &str) -> Option<Entry> {
+        let s: Vec<_> = line.splitn(3, '|').collect();
+        if s.len() != 3 || s[2].is_empty() {
+            return None;
+        }
+        Some(Entry {
+            rank: s[0].parse().ok()?,
+            time: s[1].parse().ok()?,
+            path: s[2].to_string(),
+        })
+    }
+
+    fn score(&self, now: u64) -> f64 {
+        let age = now.saturating_sub(self.time);
+        let factor = if age < HOUR {
+            4.0
+        } else if age < DAY {
+            2.0
+        } else if age < WEEK {
+            0.5
+        } else {
+            0.25
+        };
+        self.rank * factor
+    }
+
+    // all the keywords are found in the path in order, and the last one in the last component
+    fn matches(&self, keywords: &[String]) -> bool {
+        let path = self.path.to_lowercase();
+        let mut rest = path.as_str();
+        for k in keywords.iter() {
+            match rest.find(k.as_str()) {
+                Some(i) => rest = &rest[i + k.len()..],
+                None => return false,
+            }
+        }
+        match (keywords.last(), path.rfind('/')) {
+            (Some(k), Some(i)) => path[i + 1..].contains(k.as_str()),
+            _ => true,
+        }
+    }
+}
+
+fn now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|it| it.as_secs())
+        .unwrap_or(0)
+}
+
+#[derive(Clone)]
+pub struct Frecency {
+    path: PathBuf,
+    entries: Vec<Entry>,
+    // the entries are changed since saved
+    dirty: bool,
+    saved: Instant,
+}
+
+impl Frecency {
+    pub fn new(home: &Path) -> Frecency {
+        let path = home.join(".config/fff/frecency");
+        let entries = fs::read_to_string(&path)
+            .map(|s| s.lines().filter_map(Entry::parse).collect())
+            .unwrap_or_default();
+        Frecency {
+            path,
+            entries,
+            dirty: false,
+            saved: Instant::now(),
+        }
+    }
+
+    // the dir is entered
+    pub fn add(&mut self, dir: &str) {
+        let now = now();
+        match self.entries.iter_mut().find(|it| it.path == dir) {
+            Some(e) => {
+                e.rank += 1.0;
+                e.time = now;
+            }
+            None => self.entries.push(Entry {
+                path: dir.to_string(),
+                rank: 1.0,
+                time: now,
+            }),
+        }
+
+        let total: f64 = self.entries.iter().map(|it| it.rank).sum();
+        if total > MAX_AGE {
+            self.entries.iter_mut().for_each(|it| it.rank *= AGING);
+            self.entries.retain(|it| it.rank >= 1.0);
+        }
+        self.dirty = true;
+        if self.saved.elapsed() >= SAVE_INTERVAL {
+            self.save();
+        }
+    }
+
+    // the existing dirs matching the fragments typed, the best first, `except` is left out
+    pub fn query(&self, text: &str, except: &str) -> Vec<String> {
+        let keywords: Vec<_> = text
+            .split_whitespace()
+            .map(|it| it.to_lowercase())
+            .collect();
+        let now = now();
+        let mut found: Vec<_> = self
+            .entries
+            .iter()
+            .filter(|it| it.path != except && it.matches(&keywords))
+            .map(|it| (it.score(now), it))
+            .collect();
+        found.sort_by(|a, b| b.0.partial_cmp(&a.0).unwrap());
+        found
+            .into_iter()
+            .map(|(_, it)| it.path.clone())
+            .filter(|it| Path::new(it).is_dir())
+            .collect()
+    }
+
+    // write the entries if they are changed since saved
+    pub fn save(&mut self) {
+        if !self.dirty {
+            return;
+        }
+        self.dirty = false;
+        self.saved = Instant::now();
+
+        let content: String = self
+            .entries
+            .iter()
+            .map(|it| format!("{}|{}|{}\n", it.rank, it.time, it.path))
+            .collect();
+        let re = fs::create_dir_all(self.path.parent().unwrap())
+            .and_then(|_| fs::write(&self.path, content));
+        if let Err(e) = re {
+            log::error!("write frecency {} failed: {:?}", self.path.display(), e);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(path: &str, rank: f64, time: u64) -> Entry {
+        Entry {
+            path: path.to_string(),
+            rank,
+            time,
+        }
+    }
+
+    fn home() -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("fff-frecency-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        for d in &["src/fff", "src/other", "docs/fff"] {
+            fs::create_dir_all(dir.join(d)).unwrap();
+        }
+        dir
+    }
+
+    #[test]
+    fn scores_by_age() {
+        let now = 10 * WEEK;
+        assert_eq!(entry("/a", 2.0, now - 10).score(now), 8.0);
+        assert_eq!(entry("/a", 2.0, now - 2 * HOUR).score(now), 4.0);
+        assert_eq!(entry("/a", 2.0, now - 2 * DAY).score(now), 1.0);
+        assert_eq!(entry("/a", 2.0, now - 2 * WEEK).score(now), 0.5);
+        assert_eq!(entry("/a", 2.0, now + 10).score(now), 8.0);
+    }
+
+    #[test]
+    fn matches_fragments() {
+        let e = entry("/home/me/Src/FFF", 1.0, 0);
+        let words = |s: &str| -> Vec<String> { s.split(' ').map(|it| it.to_string()).collect() };
+        assert!(e.matches(&words("src fff")));
+        assert!(e.matches(&words("ff")));
+        assert!(!e.matches(&words("fff src")));
+        assert!(!e.matches(&words("src")));
+        assert!(e.matches(&[]));
+    }
+
+    #[test]
+    fn queries_ranked_dirs() {
+        let home = home();
+        let dir = |d: &str| home.join(d).display().to_string();
+        let mut f = Frecency::new(&home);
+        for _ in 0..3 {
+            f.add(&dir("docs/fff"));
+        }
+        f.add(&dir("src/fff"));
+        f.add(&dir("src/other"));
+        f.add(&dir("gone/fff"));
+
+        assert_eq!(f.query("fff", ""), vec![dir("docs/fff"), dir("src/fff")]);
+        assert_eq!(f.query("src fff", ""), vec![dir("src/fff")]);
+        assert_eq!(f.query("fff", &dir("docs/fff")), vec![dir("src/fff")]);
+        assert_eq!(f.query("", "").len(), 3);
+
+        assert!(!f.path.exists());
+        f.save();
+        let read = Frecency::new(&home);
+        assert_eq!(read.query("fff", ""), f.query("fff", ""));
+        fs::remove_dir_all(&home).unwrap();
+    }
+}
@@ -1,6 +1,7 @@
 use crate::model::context::Context;
 use crate::model::file::{InnerFile, Op};
-use crate::model::result::Res;
+use crate::model::result::{Res, Void};
+use crate::model::state::history::{History, Location};
 use crate::model::state::list::list::FileList;
 use crate::model::state::list::{MarkerTrait, SelectorTrait};
 use crate::model::state::workspace::ViewMode;
@@ -10,15 +11,57 @@ use std::sync::Arc;
 
 pub struct Group {
     file_list: Vec<FileList>,
+    pub history: History,
 }
 
 impl Group {
     pub fn new() -> Group {
         Group {
             file_list: vec![FileList::new()],
+            history: History::new(),
         }
     }
 
+    pub fn location(&self) -> Location {
+        Location {
+            dirs: self
+                .file_list
+                .iter()
+                .filter_map(|it| it.dir().map(|d| d.info().inner.clone()))
+                .collect(),
+            selected: self
+                .current()
+                .selected_file()
+                .map(|it| it.info().name.clone()),
+        }
+    }
+
+    // rebuild the lists of the location, every list selects the dir shown in the next one
+    pub async fn restore(&mut self, loc: &Location, ctx: &Context) -> Void {
+        let mut lists: Vec<FileList> = Vec::new();
+        for dir in loc.dirs.iter() {
+            let mut fl = FileList::new();
+            fl.update(dir.clone(), ctx).await?;
+            let name = fl.dir().map(|it| it.info().name.clone());
+            if let (Some(prev), Some(n)) = (lists.last_mut(), name) {
+                prev.select_by_name(&n);
+            }
+            lists.push(fl);
+        }
+
+        if let (Some(fl), Some(n)) = (lists.last_mut(), &loc.selected) {
+            fl.select_by_name(n);
+        }
+        if !lists.is_empty() {
+            self.file_list = lists;
+        }
+        Ok(())
+    }
+
+    pub fn lists_mut(&mut self) -> impl Iterator<Item = &mut FileList> {
+        self.file_list.iter_mut()
+    }
+
     pub async fn add_file_list(
         &mut self,
         file: Arc<InnerFile>,
@@ -33,6 +76,14 @@ impl Group {
         Ok(self.current_mut())
     }
 
+    // replace all the lists with the one showing `file`
+    pub async fn open(&mut self, file: Arc<InnerFile>, ctx: &Context) -> Res<&mut FileList> {
+        let mut fl = FileList::new();
+        fl.update_dir(file, ctx).await?;
+        self.file_list = vec![fl];
+        Ok(self.current_mut())
+    }
+
     pub async fn close_last(&mut self, ctx: &Context) -> Res<(bool, Option<Vec<FileItem>>)> {
         if self.file_list.len() == 1 {
             return match self.current_mut().dir() {
@@ -49,6 +100,13 @@ impl Group {
         Ok((true, None))
     }
 
+    pub async fn refresh(&mut self, ctx: &Context) -> Void {
+        for fl in self.file_list.iter_mut() {
+            fl.refresh(ctx).await?;
+        }
+        Ok(())
+    }
+
     pub fn current(&self) -> &FileList {
         &self.file_list.last().unwrap()
     }
@@ -81,3 +139,89 @@ impl Group {
         self.file_list.iter().map(f).collect()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::file::mem::{self, MemFs};
+    use std::path::Path;
+
+    fn sample(host: &str) -> MemFs {
+        let fs = mem::mount(Path::new(host));
+        fs.create_dir_all("/a/b").unwrap();
+        fs.write("/a/b/one.txt", b"one").unwrap();
+        fs.write("/a/two.txt", b"two").unwrap();
+        fs
+    }
+
+    fn dirs(g: &Group) -> Vec<String> {
+        g.location().dirs.iter().map(|it| it.to_string()).collect()
+    }
+
+    #[tokio::test]
+    async fn open_and_close_lists() {
+        let fs = sample("/mem/group-open");
+        let ctx = Context::for_test(Path::new("/no/such/home"));
+        let mut g = Group::new();
+        g.open(Arc::new(fs.get("/").unwrap()), &ctx).await.unwrap();
+        g.add_file_list(Arc::new(fs.get("/a").unwrap()), &ViewMode::InColumn, &ctx)
+            .await
+            .unwrap();
+        assert_eq!(
+            dirs(&g),
+            vec![
+                fs.inner_path("/").to_string(),
+                fs.inner_path("/a").to_string()
+            ]
+        );
+
+        // a list shows the dir in place of the current one
+        g.add_file_list(Arc::new(fs.get("/a/b").unwrap()), &ViewMode::InList, &ctx)
+            .await
+            .unwrap();
+        assert_eq!(g.current_path(), fs.inner_path("/a/b").to_string());
+        assert_eq!(g.location().dirs.len(), 2);
+
+        let (closed, items) = g.close_last(&ctx).await.unwrap();
+        assert!(closed && items.is_none());
+        assert_eq!(g.current_path(), fs.inner_path("/").to_string());
+
+        // the only list goes to the parent dir
+        g.open(Arc::new(fs.get("/a/b").unwrap()), &ctx)
+            .await
+            .unwrap();
+        let (closed, items) = g.close_last(&ctx).await.unwrap();
+        assert!(closed);
+        assert_eq!(items.unwrap().len(), 2);
+        assert_eq!(dirs(&g), vec![fs.inner_path("/a").to_string()]);
+        mem::unmount(Path::new("/mem/group-open"));
+    }
+
+    #[tokio::test]
+    async fn restore_location() {
+        let fs = sample("/mem/group-restore");
+        let ctx = Context::for_test(Path::new("/no/such/home"));
+        let loc = Location {
+            dirs: vec![fs.inner_path("/"), fs.inner_path("/a")],
+            selected: Some("two.txt".to_string()),
+        };
+        let mut g = Group::new();
+        g.restore(&loc, &ctx).await.unwrap();
+        assert_eq!(
+            dirs(&g),
+            vec![loc.dirs[0].to_string(), loc.dirs[1].to_string()]
+        );
+        assert_eq!(g.location().selected, Some("two.txt".to_string()));
+
+        fs.write("/a/three.txt", b"three").unwrap();
+        g.refresh(&ctx).await.unwrap();
+        assert_eq!(g.current().files().len(), 3);
+        assert_eq!(g.location().selected, Some("two.txt".to_string()));
+
+        // nothing is changed if a dir of the location is gone
+        fs.remove("/a").unwrap();
+        assert!(g.restore(&loc, &ctx).await.is_err());
+        assert_eq!(g.current_path(), loc.dirs[1].to_string());
+        mem::unmount(Path::new("/mem/group-restore"));
+    }
+}
@@ -0,0 +1,180 @@
+use crate::model::file::path::InnerPath;
+
+const MAX_HISTORY: usize = 100;
+
+// where a group is, the dirs of its lists from the first column, and the name selected in the
+// last one
+#[derive(Clone)]
+pub struct Location {
+    pub dirs: Vec<InnerPath>,
+    pub selected: Option<String>,
+}
+
+impl Location {
+    fn same(&self, other: &Location) -> bool {
+        self.selected == other.selected
+            && self.dirs.len() == other.dirs.len()
+            && self
+                .dirs
+                .iter()
+                .zip(other.dirs.iter())
+                .all(|(a, b)| a.to_string() == b.to_string())
+    }
+
+    pub fn desc(&self) -> String {
+        let dir = self
+            .dirs
+            .last()
+            .map_or("-".to_string(), |it| it.to_string());
+        match &self.selected {
+            Some(s) => format!("{}  {}", dir, s),
+            None => dir,
+        }
+    }
+}
+
+// the back and the forward stacks of the locations of a group
+pub struct History {
+    back: Vec<Location>,
+    forward: Vec<Location>,
+}
+
+impl History {
+    pub fn new() -> Self {
+        History {
+            back: Vec::new(),
+            forward: Vec::new(),
+        }
+    }
+
+    // remember the location left, the forward ones are dropped as a new way is taken
+    pub fn push(&mut self, loc: Location) {
+        self.forward.clear();
+        if self.back.last().map(|it| it.same(&loc)).unwrap_or(false) {
+            return;
+        }
+        self.back.push(loc);
+        if self.back.len() > MAX_HISTORY {
+            self.back.remove(0);
+        }
+    }
+
+    pub fn peek(&self, forward: bool) -> Option<&Location> {
+        if forward {
+            self.forward.last()
+        } else {
+            self.back.last()
+        }
+    }
+
+    // go to the peeked location, the current one is kept in the other stack
+    pub fn step(&mut self, forward: bool, current: Location) {
+        let (from, to) = if forward {
+            (&mut self.forward, &mut self.back)
+        } else {
+            (&mut self.back, &mut self.forward)
+        };
+        if from.pop().is_some() {
+            to.push(current);
+        }
+    }
+
+    // drop the peeked location, which can not be gone to any more
+    pub fn drop_peeked(&mut self, forward: bool) {
+        if forward {
+            self.forward.pop();
+        } else {
+            self.back.pop();
+        }
+    }
+
+    // the locations gone back to by the back action, the most recent first
+    pub fn recent(&self) -> Vec<Location> {
+        self.back.iter().rev().cloned().collect()
+    }
+}
+
+// the lines to choose one of the locations by its number, from 1
+pub fn numbered(locations: &[Location]) -> Vec<String> {
+    locations
+        .iter()
+        .enumerate()
+        .map(|(i, it)| format!("{} {}", i + 1, it.desc()))
+        .collect()
+}
+
+// the location chosen by the number typed, None if there is no such number
+pub fn chosen<'a>(locations: &'a [Location], input: &str) -> Option<&'a Location> {
+    match input.trim().parse::<usize>() {
+        Ok(i) if i >= 1 => locations.get(i - 1),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::Path;
+
+    fn loc(dir: &str, selected: Option<&str>) -> Location {
+        Location {
+            dirs: vec![InnerPath::new_protocol(
+                Path::new("/a.zip"),
+                "zip",
+                Path::new(dir),
+            )],
+            selected: selected.map(|it| it.to_string()),
+        }
+    }
+
+    fn dir(l: Option<&Location>) -> Option<String> {
+        l.map(|it| it.desc())
+    }
+
+    #[test]
+    fn push_skips_repeats() {
+        let mut h = History::new();
+        h.push(loc("/x", None));
+        h.push(loc("/x", None));
+        h.push(loc("/x", Some("f")));
+        assert_eq!(h.recent().len(), 2);
+        for i in 0..MAX_HISTORY + 10 {
+            h.push(loc(&format!("/{}", i), None));
+        }
+        assert_eq!(h.recent().len(), MAX_HISTORY);
+    }
+
+    #[test]
+    fn step_back_and_forth() {
+        let mut h = History::new();
+        h.push(loc("/a", None));
+        h.push(loc("/b", None));
+        assert!(h.peek(true).is_none());
+
+        h.step(false, loc("/c", None));
+        assert_eq!(dir(h.peek(false)), dir(Some(&loc("/a", None))));
+        assert_eq!(dir(h.peek(true)), dir(Some(&loc("/c", None))));
+
+        h.step(true, loc("/b", None));
+        assert_eq!(dir(h.peek(false)), dir(Some(&loc("/b", None))));
+        assert!(h.peek(true).is_none());
+
+        h.step(false, loc("/c", None));
+        h.push(loc("/d", None));
+        assert!(h.peek(true).is_none());
+        h.drop_peeked(false);
+        assert_eq!(dir(h.peek(false)), dir(Some(&loc("/a", None))));
+    }
+
+    #[test]
+    fn choose_numbered() {
+        let ls = vec![loc("/a", Some("f")), loc("/b", None)];
+        let lines = numbered(&ls);
+        assert_eq!(lines.len(), 2);
+        assert!(lines[0].starts_with("1 ") && lines[0].ends_with("  f"));
+        assert_eq!(dir(chosen(&ls, " 2 ")), dir(Some(&ls[1])));
+        assert!(chosen(&ls, "0").is_none());
+        assert!(chosen(&ls, "3").is_none());
+        assert!(chosen(&ls, "x").is_none());
+    }
+}
@@ -83,7 +83,7 @@ create_enum!(TimeUnit: H, D, M);
 create_enum!(SizeUnit: K, M, G);
 
 impl TimeUnit {
-    fn to_seconds(&self, n: &u64) -> u64 {
+    fn to_seconds(self, n: &u64) -> u64 {
         match self {
             Self::H => n * 60 * 60,
             Self::D => n * 24 * 60 * 60,
@@ -93,7 +93,7 @@ impl TimeUnit {
 }
 
 impl SizeUnit {
-    fn to_bytes(&self, n: &u64) -> u64 {
+    fn to_bytes(self, n: &u64) -> u64 {
         match self {
             Self::K => n * 1024,
             Self::M => n * 1024 * 1024,
@@ -160,7 +160,7 @@ impl FilterItem {
                 }
             }
             Self::Size(gt, n, u) => {
-                let ss = SizeUnit::to_bytes(u, n);
+                let ss = SizeUnit::to_bytes(*u, n);
                 if gt.clone() {
                     fi.size > ss
                 } else {
@@ -168,7 +168,7 @@ impl FilterItem {
                 }
             }
             Self::MTime(n, u) => {
-                let sec = Duration::from_secs(TimeUnit::to_seconds(u, n));
+                let sec = Duration::from_secs(TimeUnit::to_seconds(*u, n));
                 let now = SystemTime::now().sub(sec);
 
                 match fi.modified {
@@ -76,6 +76,67 @@ impl FileList {
         return Err(Error::DirIsRequired(file.path_str()));
     }
 
+    // list the dir again, the selection is kept by name and the marks are cleared
+    pub async fn refresh(&mut self, ctx: &Context) -> Void {
+        let dir = match &self.dir {
+            Some(d) => d.clone(),
+            None => return Ok(()),
+        };
+        let selected = self.selected();
+        let name = self.selected_file().map(|it| it.info().name.clone());
+
+        self.update_dir(dir, ctx).await?;
+        self.clear_mark();
+        let found = match name {
+            Some(n) => self.select_by_name(&n),
+            None => false,
+        };
+        if let (false, Some(idx)) = (found, selected) {
+            let len = self.sorter.lock().unwrap().get_files().len();
+            self.select(std::cmp::min(idx, len.saturating_sub(1)));
+        }
+        Ok(())
+    }
+
+    // the marked files, or the selected one if nothing is marked
+    pub fn marked_files(&self) -> FileVec {
+        let fs = self.sorter.lock().unwrap().get_files().clone();
+        let marked = self.marked();
+        if marked.is_empty() {
+            return self.selected_file().into_iter().collect();
+        }
+        marked
+            .into_iter()
+            .filter_map(|it| fs.get(it).cloned())
+            .collect()
+    }
+
+    // sort the files again, the selection and the marks are kept by name
+    pub fn resort(&mut self) {
+        let fs = self.files();
+        let selected = self.selected_file().map(|it| it.info().name.clone());
+        let marked: Vec<_> = self
+            .marked()
+            .into_iter()
+            .filter_map(|it| fs.get(it).map(|f| f.info().name.clone()))
+            .collect();
+
+        self.sorter.lock().unwrap().resort();
+        self.clear_mark();
+        for (i, f) in self.files().iter().enumerate() {
+            if marked.contains(&f.info().name) {
+                self.mark(i);
+            }
+        }
+        if let Some(n) = selected {
+            self.select_by_name(&n);
+        }
+    }
+
+    pub fn files(&self) -> FileVec {
+        self.sorter.lock().unwrap().get_files().clone()
+    }
+
     pub fn subscribe_file_change<F: Fn(&FileVec) + 'static + Send + Sync>(&self, f: F) {
         self.sorter.lock().unwrap().subscribe_change(f);
     }
@@ -153,3 +214,89 @@ impl MarkerTrait for FileList {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::file::mem::{self, MemFs};
+    use std::path::Path;
+
+    fn names(fl: &FileList) -> Vec<String> {
+        fl.files().iter().map(|it| it.info().name.clone()).collect()
+    }
+
+    fn sample(host: &str) -> MemFs {
+        let fs = mem::mount(Path::new(host));
+        fs.create_dir_all("/d").unwrap();
+        fs.write("/b.txt", b"bb").unwrap();
+        fs.write("/a.txt", b"a").unwrap();
+        fs.write("/c.txt", b"ccc").unwrap();
+        fs.write("/.hidden", b"").unwrap();
+        fs
+    }
+
+    #[tokio::test]
+    async fn filter_and_sort() {
+        let fs = sample("/mem/list-sort");
+        let ctx = Context::for_test(Path::new("/no/such/home"));
+        let mut fl = FileList::new();
+        fl.update(fs.inner_path("/"), &ctx).await.unwrap();
+        assert_eq!(names(&fl), vec!["d", "a.txt", "b.txt", "c.txt"]);
+
+        fl.set_order(FileSortBy::SIZE);
+        assert_eq!(names(&fl), vec!["d", "c.txt", "b.txt", "a.txt"]);
+
+        fl.set_show_hidden(true);
+        assert_eq!(names(&fl), vec!["d", "c.txt", "b.txt", "a.txt", ".hidden"]);
+
+        fl.set_filter(":f b".to_string()).unwrap();
+        assert_eq!(names(&fl), vec!["b.txt"]);
+        mem::unmount(Path::new("/mem/list-sort"));
+    }
+
+    #[tokio::test]
+    async fn refresh_keeps_selection() {
+        let fs = sample("/mem/list-refresh");
+        let ctx = Context::for_test(Path::new("/no/such/home"));
+        let mut fl = FileList::new();
+        fl.update(fs.inner_path("/"), &ctx).await.unwrap();
+        assert!(fl.select_by_name("c.txt"));
+        fl.mark(1);
+
+        fs.rename("/a.txt", "/e.txt").unwrap();
+        fl.refresh(&ctx).await.unwrap();
+        assert_eq!(names(&fl), vec!["d", "b.txt", "c.txt", "e.txt"]);
+        assert_eq!(fl.selected_file().unwrap().info().name, "c.txt");
+        assert!(fl.marked().is_empty());
+
+        // the selection stays at its place when the selected file is gone
+        fs.remove("/c.txt").unwrap();
+        fl.refresh(&ctx).await.unwrap();
+        assert_eq!(fl.selected_file().unwrap().info().name, "e.txt");
+        mem::unmount(Path::new("/mem/list-refresh"));
+    }
+
+    #[tokio::test]
+    async fn marked_or_selected() {
+        let fs = sample("/mem/list-marked");
+        let ctx = Context::for_test(Path::new("/no/such/home"));
+        let mut fl = FileList::new();
+        fl.update(fs.inner_path("/"), &ctx).await.unwrap();
+        fl.select_by_name("b.txt");
+        let selected: Vec<_> = fl.marked_files().iter().map(|it| it.path_str()).collect();
+        assert_eq!(selected, vec![fs.inner_path("/b.txt").to_string()]);
+
+        fl.mark(1);
+        fl.mark(3);
+        fl.set_order(FileSortBy::SIZE);
+        fl.resort();
+        let marked: Vec<_> = fl
+            .marked_files()
+            .iter()
+            .map(|it| it.info().name.clone())
+            .collect();
+        assert_eq!(marked, vec!["c.txt", "a.txt"]);
+        assert!(fl.update(fs.inner_path("/a.txt"), &ctx).await.is_err());
+        mem::unmount(Path::new("/mem/list-marked"));
+    }
+}
@@ -1,4 +1,5 @@
 use crate::common::Publisher;
+use crate::model::file::dir_size;
 use crate::model::state::list::{FileHolder, FileSortBy, FileVec, SorterTrait};
 
 use std::cmp::Ordering;
@@ -35,6 +36,11 @@ impl FileSorter {
         self.do_sort();
     }
 
+    // sort again after the sizes of the dirs are computed
+    pub fn resort(&mut self) {
+        self.do_sort();
+    }
+
     fn do_sort(&mut self) {
         self.sorted = self.files.iter().map(|it| it.clone()).collect();
         let order = &self.order;
@@ -49,7 +55,7 @@ impl FileSorter {
 
             return match order {
                 FileSortBy::NAME => a.info().name.cmp(&b.info().name),
-                FileSortBy::SIZE => b.info().size.cmp(&a.info().size),
+                FileSortBy::SIZE => dir_size::size(b.info()).cmp(&dir_size::size(a.info())),
                 FileSortBy::MTIME => b.info().modified.cmp(&a.info().modified),
             };
         });
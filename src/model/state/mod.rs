@@ -1,4 +1,7 @@
 mod bookmark;
+mod clip;
+mod frecency;
 mod group;
+mod history;
 pub mod list;
 pub mod workspace;
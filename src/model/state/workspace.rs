@@ -1,22 +1,44 @@
 use crate::common::Functional;
-use crate::kbd::Kbd;
+use crate::config::enums::{BindingType, CommandMode};
+use crate::config::OpenRule;
+use crate::kbd::{Answer, Kbd};
 use crate::model::context::Context;
+use crate::model::file::archive::{self, pack};
+use crate::model::file::attr::{self, Attribute};
+use crate::model::file::cmd::{self, Placeholders};
+use crate::model::file::conflict::{conflict_line, error_desc, file_name};
+use crate::model::file::dir_size;
 use crate::model::file::path::InnerPath;
-use crate::model::file::InnerFile;
-use crate::model::result::Void;
+use crate::model::file::pattern::Pattern;
+use crate::model::file::{
+    conflict, copy_all, link, link_target, measure, mem, move_all, opener, protocol, rename,
+    revision_path, trash, LinkKind,
+};
+use crate::model::file::{InnerFile, Op};
+use crate::model::journal::{self, Journal, Step};
+use crate::model::result::{Error, Res, Void};
 use crate::model::state::bookmark::Bookmark;
+use crate::model::state::clip::Clip;
+use crate::model::state::frecency::Frecency;
 use crate::model::state::group::Group;
+use crate::model::state::history::{self, Location};
 use crate::model::state::list::list::FileList;
-use crate::model::state::list::{FileSortBy, FilterTrait, MarkerTrait, SelectorTrait, SorterTrait};
+use crate::model::state::list::{
+    FileSortBy, FileVec, FilterTrait, MarkerTrait, SelectorTrait, SorterTrait,
+};
+use crate::model::task::{Progress, TaskManager};
 use crate::ui::event::UIEvent::{
-    AddFileList, Message, RefreshFileItem, RemoveFileList, SetBookmark, SetMark, SetPath,
-    SetSelect, SetShowDetail, SwitchTab,
+    AddFileList, Message, RefreshFileItem, RemoveFileList, SetBookmark, SetClipCount, SetMark,
+    SetPath, SetPreview, SetSelect, SetShowDetail, ShowTaskDetail, SwitchTab,
 };
 use crate::ui::event::{FileItem, UIEventSender};
 use std::borrow::Borrow;
 use std::convert::TryFrom;
+use std::fs;
 use std::path::PathBuf;
+use std::process::Stdio;
 use std::sync::Arc;
+use std::time::Duration;
 
 pub enum ViewMode {
     InColumn,
@@ -25,15 +47,108 @@ pub enum ViewMode {
 
 const MAX_GROUP_COUNT: usize = 4;
 
+const FAKE_TASK_STEPS: u64 = 20;
+
+// the ranked dirs shown while the jump is typed
+const MAX_JUMP_CANDIDATES: usize = 20;
+
+// the path of a file, None if it is not a local file
+fn local_path(file: &InnerFile) -> Option<String> {
+    match file.info().protocol {
+        Some(_) => None,
+        None => Some(file.info().path.display().to_string()),
+    }
+}
+
+fn files_desc(files: &FileVec) -> String {
+    match files.as_slice() {
+        [f] => f.info().name.clone(),
+        fs => format!("{} files", fs.len()),
+    }
+}
+
+// copy or move the files to the targets, the existing targets are moved to the trash first
+// if `overwrite`, the done steps are kept in `steps`
+fn paste_all(
+    jobs: Vec<(PathBuf, PathBuf, bool)>,
+    moving: bool,
+    p: &Progress,
+    steps: &mut Vec<Step>,
+) -> Void {
+    for (from, _, _) in jobs.iter() {
+        let (bytes, items) = measure(from)?;
+        p.add_total(bytes, items);
+    }
+
+    let mut re = Ok(());
+    for (from, to, overwrite) in jobs {
+        p.check()?;
+        let r = if overwrite {
+            trash::trash(&to).map(|trashed| {
+                steps.push(Step::Trash {
+                    from: to.clone(),
+                    trashed,
+                })
+            })
+        } else {
+            Ok(())
+        }
+        .and_then(|_| {
+            if moving {
+                move_all(&from, &to, p)?;
+                steps.push(Step::Move {
+                    from: from.clone(),
+                    to: to.clone(),
+                });
+            } else {
+                copy_all(&from, &to, p)?;
+                steps.push(Step::Copy {
+                    from: from.clone(),
+                    to: to.clone(),
+                });
+            }
+            Ok(())
+        });
+        match r {
+            Err(Error::Cancelled) => return r,
+            Err(e) => {
+                log::error!("paste {} failed: {:?}", from.display(), e);
+                re = re.and(Err(e));
+            }
+            Ok(_) => {}
+        }
+    }
+    re
+}
+
+// ask a yes/no question for one of multiple files, `all` keeps the "to all" answer for
+// the rest of them, None is returned if the question is aborted
+async fn confirm(ctx: &Context, msg: &str, multiple: bool, all: &mut Option<bool>) -> Option<bool> {
+    if let Some(v) = all {
+        return Some(*v);
+    }
+    match ctx.request_answer(msg, multiple).await? {
+        Answer::Yes => Some(true),
+        Answer::No => Some(false),
+        Answer::YesToAll => Some(*all.get_or_insert(true)),
+        Answer::NoToAll => Some(*all.get_or_insert(false)),
+    }
+}
+
 pub struct Workspace {
     enter_path: InnerPath,
     home_path: InnerPath,
     current_group: usize,
+    // the group switched from, the other dir of the custom commands
+    last_group: usize,
     current_mode: ViewMode,
     show_detail: bool,
     groups: Vec<Group>,
     ui_event: UIEventSender,
     bookmark: Bookmark,
+    frecency: Frecency,
+    clip: Clip,
+    tasks: TaskManager,
     context: Arc<Context>,
     kbd: Arc<Kbd>,
 }
@@ -46,16 +161,21 @@ impl Workspace {
         kbd: Arc<Kbd>,
     ) -> Self {
         let bookmark = Bookmark::new(&home_path);
+        let tasks = TaskManager::new(ui_event.clone(), kbd.clone());
         Workspace {
             enter_path: InnerPath::try_from(enter_path.display().to_string()).unwrap(),
             home_path: InnerPath::try_from(home_path.display().to_string()).unwrap(),
             current_group: 0,
+            last_group: 0,
             current_mode: ViewMode::InColumn,
             show_detail: false,
             groups: Vec::new(),
             ui_event,
             bookmark,
-            context: Arc::new(Context::new(kbd.clone())),
+            frecency: Frecency::new(&home_path),
+            clip: Clip::new(),
+            tasks,
+            context: Arc::new(Context::new(kbd.clone(), Journal::new(&home_path))),
             kbd,
         }
     }
@@ -81,6 +201,9 @@ impl Workspace {
             tab
         };
 
+        if t != self.current_group {
+            self.last_group = self.current_group;
+        }
         self.current_group = t;
         let current = &self.groups[t];
         self.ui_event.start_queue().unwrap();
@@ -113,10 +236,12 @@ impl Workspace {
 
     pub async fn close_right(&mut self) -> Void {
         let ctx = self.context.clone();
+        let loc = self.current().location();
         let (succ, vs) = self.current_mut().close_last(ctx.borrow()).await?;
         if !succ {
             return Ok(());
         }
+        self.current_mut().history.push(loc);
 
         let some = vs.is_some();
         self.ui_event.send(RemoveFileList(vs))?;
@@ -166,7 +291,14 @@ impl Workspace {
         let ctx = self.context.clone();
         match of {
             Some(file) => {
-                if file.is_dir() {
+                let dir = if file.is_dir() {
+                    Some(file.clone())
+                } else {
+                    protocol::enter(&file).transpose()?.map(Arc::new)
+                };
+
+                if let Some(dir) = dir {
+                    let loc = self.current().location();
                     let mode = match self.current_mode {
                         ViewMode::InColumn => ViewMode::InColumn,
                         ViewMode::InList => ViewMode::InList,
@@ -175,9 +307,11 @@ impl Workspace {
                     let sender = self.ui_event.clone();
                     let fl = self
                         .current_mut()
-                        .add_file_list(file.clone(), &mode, ctx.borrow())
+                        .add_file_list(dir, &mode, ctx.borrow())
                         .await?;
                     Workspace::bind_list(&sender, fl);
+                    self.current_mut().history.push(loc);
+                    self.enter_dir();
                     let vs = self.current_list().file_items();
                     self.ui_event.batch_send(vec![
                         SetPath(self.current().current_path()),
@@ -197,6 +331,289 @@ impl Workspace {
         Ok(())
     }
 
+    pub async fn open_path(&mut self, path: InnerPath) -> Void {
+        let file = Arc::new(InnerFile::try_from(path)?);
+        let loc = self.current().location();
+        let ctx = self.context.clone();
+        let sender = self.ui_event.clone();
+        let fl = self.current_mut().open(file, ctx.borrow()).await?;
+        Workspace::bind_list(&sender, fl);
+        self.current_mut().history.push(loc);
+        self.enter_dir();
+        self.current().sync_to_ui(&self.ui_event)?;
+        Ok(())
+    }
+
+    // record the current dir of the current group in the frecency database when it is opened,
+    // going up or back is not counted, the dirs in the archives or the revisions are left out
+    fn enter_dir(&mut self) {
+        let path = match self.current_list().dir() {
+            Some(d) if d.info().protocol.is_none() => d.path_str(),
+            _ => return,
+        };
+        self.frecency.add(&path);
+    }
+
+    // save the state kept across the sessions, before fff exits
+    pub fn save(&mut self) {
+        self.frecency.save();
+    }
+
+    // jump to the best dir matching the fragments typed, the ranked dirs are shown while typed
+    pub async fn jump_frecent(&mut self) -> Void {
+        let current = self.current().current_path();
+        let frecency = self.frecency.clone();
+        let except = current.clone();
+        let ui_event = self.ui_event.clone();
+        let input = self
+            .context
+            .request_live_input(
+                "Jump to",
+                Box::new(move |text| {
+                    let lines = frecency
+                        .query(text, &except)
+                        .into_iter()
+                        .take(MAX_JUMP_CANDIDATES)
+                        .enumerate()
+                        .map(|(i, it)| format!("{} {}", i + 1, it))
+                        .collect();
+                    ui_event.send(SetPreview(Some(lines))).unwrap();
+                }),
+            )
+            .await;
+        self.ui_event.send(SetPreview(None))?;
+        let input = match input {
+            Some(i) => i,
+            None => return Ok(()),
+        };
+
+        match self.frecency.query(&input, &current).first() {
+            Some(dir) => self.open_path(InnerPath::try_from(dir.clone())?).await,
+            None => {
+                self.ui_event
+                    .send(Message(format!("No dir matches {}", input.trim())))?;
+                Ok(())
+            }
+        }
+    }
+
+    // go back or forward in the history of the current group
+    pub async fn go_back(&mut self, forward: bool) -> Void {
+        let loc = match self.current().history.peek(forward) {
+            Some(l) => l.clone(),
+            None => {
+                let msg = if forward {
+                    "No next dir"
+                } else {
+                    "No previous dir"
+                };
+                self.ui_event.send(Message(msg.to_string()))?;
+                return Ok(());
+            }
+        };
+        let current = self.current().location();
+        if self.restore_location(&loc).await? {
+            self.current_mut().history.step(forward, current);
+        } else {
+            self.current_mut().history.drop_peeked(forward);
+        }
+        Ok(())
+    }
+
+    // choose one of the recent locations of the current group to go to
+    pub async fn show_history(&mut self) -> Void {
+        let recent = self.current().history.recent();
+        if recent.is_empty() {
+            self.ui_event.send(Message("No history yet".to_string()))?;
+            return Ok(());
+        }
+
+        let lines = history::numbered(&recent);
+        self.ui_event.send(SetPreview(Some(lines)))?;
+        let input = self.context.request_input("Go to (number)").await;
+        self.ui_event.send(SetPreview(None))?;
+        let input = match input {
+            Some(i) if !i.trim().is_empty() => i.trim().to_string(),
+            _ => return Ok(()),
+        };
+
+        let loc = match history::chosen(&recent, &input) {
+            Some(l) => l,
+            None => {
+                self.ui_event
+                    .send(Message(format!("No location numbered {}", input)))?;
+                return Ok(());
+            }
+        };
+        let current = self.current().location();
+        if self.restore_location(loc).await? {
+            self.current_mut().history.push(current);
+        }
+        Ok(())
+    }
+
+    // show the location in the current group, false if it can not be shown any more
+    async fn restore_location(&mut self, loc: &Location) -> Res<bool> {
+        let ctx = self.context.clone();
+        if let Err(e) = self.current_mut().restore(loc, ctx.borrow()).await {
+            self.ui_event.send(Message(format!(
+                "Can not go to {}, {}",
+                loc.desc(),
+                error_desc(&e)
+            )))?;
+            return Ok(false);
+        }
+
+        let sender = self.ui_event.clone();
+        self.current_mut()
+            .lists_mut()
+            .for_each(|it| Workspace::bind_list(&sender, it));
+        self.current().sync_to_ui(&self.ui_event)?;
+        Ok(true)
+    }
+
+    // jump to the dir of the target of the selected symlink and select the target
+    pub async fn follow_link(&mut self) -> Void {
+        let info = match self.current_list().selected_file() {
+            Some(f) if f.info().protocol.is_none() => f.info().clone(),
+            _ => return Ok(()),
+        };
+        let target = match info.link {
+            Some(l) => PathBuf::from(l.target),
+            None => {
+                self.ui_event
+                    .send(Message(format!("{} is not a symlink", info.name)))?;
+                return Ok(());
+            }
+        };
+        let dir = target.parent().map(fs::canonicalize);
+        let dir = match dir {
+            Some(Ok(d)) => d,
+            _ => {
+                self.ui_event.send(Message(format!(
+                    "The dir of {} does not exist",
+                    target.display()
+                )))?;
+                return Ok(());
+            }
+        };
+
+        self.open_path(InnerPath::try_from(dir)?).await?;
+        let found = match target.file_name() {
+            Some(n) => self.current_list_mut().select_by_name(&n.to_string_lossy()),
+            None => false,
+        };
+        if !found {
+            self.ui_event
+                .send(Message(format!("{} does not exist", target.display())))?;
+        }
+        Ok(())
+    }
+
+    // open the selected file by the opener rules, a dir or an archive is opened in the list
+    pub async fn open_file(&mut self) -> Void {
+        let file = match self.current_list().selected_file() {
+            Some(f) => f,
+            None => return Ok(()),
+        };
+        if file.is_dir() || protocol::supported(&file) {
+            return self.open_selected().await;
+        }
+        if let Err(e) = file.open(&self.context).await {
+            self.ui_event.send(Message(format!(
+                "Can not open {}, {}",
+                file.info().name,
+                error_desc(&e)
+            )))?;
+        }
+        Ok(())
+    }
+
+    // choose one of the matching opener rules to open the selected file, or type a command
+    pub async fn open_with(&mut self) -> Void {
+        let path = match self.current_list().selected_file() {
+            Some(f) if f.is_file() && f.info().protocol.is_none() => f.info().path.clone(),
+            _ => {
+                self.ui_event
+                    .send(Message("Only a local file can be opened with".to_string()))?;
+                return Ok(());
+            }
+        };
+
+        let ctx = self.context.clone();
+        let rules = opener::rules(&ctx, &path);
+        let lines = rules
+            .iter()
+            .enumerate()
+            .map(|(i, r)| {
+                let fork = if r.fork { " (fork)" } else { "" };
+                format!("{} {}{}", i + 1, r.run, fork)
+            })
+            .collect();
+        self.ui_event.send(SetPreview(Some(lines)))?;
+        let input = ctx.request_input("Open with (number or command)").await;
+        self.ui_event.send(SetPreview(None))?;
+        let input = match input {
+            Some(i) if !i.trim().is_empty() => i.trim().to_string(),
+            _ => return Ok(()),
+        };
+
+        let rule = match input.parse::<usize>() {
+            Ok(i) if i >= 1 && i <= rules.len() => rules[i - 1].clone(),
+            Ok(_) => {
+                self.ui_event
+                    .send(Message(format!("No rule numbered {}", input)))?;
+                return Ok(());
+            }
+            Err(_) => OpenRule {
+                ext: Vec::new(),
+                mime: None,
+                glob: None,
+                run: input,
+                fork: false,
+            },
+        };
+        if let Err(e) = opener::open_with(&rule, &path, &ctx).await {
+            self.ui_event.send(Message(format!(
+                "Can not open {}, {}",
+                file_name(&path),
+                error_desc(&e)
+            )))?;
+        }
+        Ok(())
+    }
+
+    pub async fn open_revision(&mut self) -> Void {
+        let dir = match self.current_list().dir() {
+            Some(d) if d.info().protocol.is_none() => d.info().path.clone(),
+            _ => {
+                self.ui_event.send(Message(
+                    "Revisions can only be opened from a local dir".to_string(),
+                ))?;
+                return Ok(());
+            }
+        };
+
+        if let Some(rev) = self.context.request_input("Revision").await {
+            self.open_path(revision_path(&dir, rev.trim())?).await?;
+        }
+        Ok(())
+    }
+
+    // open the scratch dir of the current dir, it lives in memory until fff exits
+    pub async fn open_scratch(&mut self) -> Void {
+        let dir = match self.current_list().dir() {
+            Some(d) if d.info().protocol.is_none() => d.info().path.clone(),
+            _ => {
+                self.ui_event.send(Message(
+                    "Scratch dirs can only be opened from a local dir".to_string(),
+                ))?;
+                return Ok(());
+            }
+        };
+        self.open_path(mem::mount(&dir).inner_path("/")).await
+    }
+
     pub async fn new_file(&self) -> Void {
         if let Some(InnerFile::Dir(d)) = self.current_list().dir() {
             d.new_file(&self.context).await?;
@@ -204,6 +621,969 @@ impl Workspace {
         Ok(())
     }
 
+    pub async fn new_dir(&mut self) -> Void {
+        if let Some(InnerFile::Dir(d)) = self.current_list().dir() {
+            d.new_dir(&self.context).await?;
+        }
+        self.refresh().await
+    }
+
+    // rename the selected file, the list is refreshed to show the new name
+    pub async fn rename(&mut self) -> Void {
+        let file = match self.current_list().selected_file() {
+            Some(f) => f,
+            None => return Ok(()),
+        };
+        if let Err(e) = file.rename(&self.context).await {
+            self.ui_event.send(Message(format!(
+                "Can not rename {}, {}",
+                file.info().name,
+                error_desc(&e)
+            )))?;
+        }
+        self.refresh().await
+    }
+
+    // open the marked files or the selected one in the editor, or the pager if not `edit`,
+    // the local files are opened together, the list is refreshed after the program exits
+    pub async fn view(&mut self, edit: bool) -> Void {
+        let (local, others): (FileVec, FileVec) = self
+            .current_list()
+            .marked_files()
+            .into_iter()
+            .filter(|it| it.is_file())
+            .partition(|it| it.info().protocol.is_none());
+        if local.is_empty() && others.is_empty() {
+            self.ui_event
+                .send(Message("No file is selected".to_string()))?;
+            return Ok(());
+        }
+
+        let ctx = self.context.clone();
+        if !local.is_empty() {
+            let paths: Vec<_> = local.iter().map(|it| it.info().path.clone()).collect();
+            let status = if edit {
+                ctx.edit(&paths).await?
+            } else {
+                ctx.view(&paths).await?
+            };
+            if !status.success() {
+                let program = if edit { "Editor" } else { "Pager" };
+                self.ui_event
+                    .send(Message(format!("{} exited with {}", program, status)))?;
+            }
+        }
+        for f in others.iter() {
+            if let InnerFile::File(f) = f.as_ref() {
+                if edit {
+                    f.edit(&ctx).await?;
+                } else {
+                    f.view(&ctx).await?;
+                }
+            }
+        }
+        self.refresh().await
+    }
+
+    pub async fn refresh(&mut self) -> Void {
+        let ctx = self.context.clone();
+        self.current_list_mut().refresh(ctx.borrow()).await
+    }
+
+    // refresh all the lists of all the groups, and show the current group again
+    pub async fn refresh_all(&mut self) -> Void {
+        let ctx = self.context.clone();
+        self.ui_event.start_queue()?;
+        for g in self.groups.iter_mut() {
+            g.refresh(ctx.borrow()).await?;
+        }
+        self.current().sync_to_ui(&self.ui_event)?;
+        self.ui_event.end_queue()?;
+        Ok(())
+    }
+
+    // run the custom command of the config named `name` in the current dir
+    pub async fn run_command(&mut self, name: &str) -> Void {
+        let command = match self.kbd.config().commands.get(name) {
+            Some(c) => c.clone(),
+            None => {
+                self.ui_event
+                    .send(Message(format!("No command named {}", name)))?;
+                return Ok(());
+            }
+        };
+        let dir = match self.current_list().dir().and_then(local_path) {
+            Some(d) => PathBuf::from(d),
+            None => {
+                self.ui_event.send(Message(
+                    "Commands can only be run in a local dir".to_string(),
+                ))?;
+                return Ok(());
+            }
+        };
+
+        let list = self.current_list();
+        let other = self.groups[self.last_group].current().dir();
+        let ph = Placeholders {
+            selected: list
+                .selected_file()
+                .and_then(|it| local_path(&it))
+                .map(PathBuf::from),
+            marked: list
+                .marked_files()
+                .iter()
+                .filter_map(|it| local_path(it))
+                .map(PathBuf::from)
+                .collect(),
+            other_dir: other
+                .and_then(local_path)
+                .map_or(dir.clone(), PathBuf::from),
+            dir,
+        };
+        let line = cmd::expand(&command.run, &ph);
+        let mut c = cmd::command(&line, &ph.dir);
+
+        let ctx = self.context.clone();
+        match command.mode {
+            CommandMode::Foreground => {
+                let status = ctx.run(c).await?;
+                if !status.success() {
+                    self.ui_event
+                        .send(Message(format!("{} exited with {}", name, status)))?;
+                }
+            }
+            CommandMode::Background => {
+                self.tasks
+                    .spawn(format!("Run {}", name), move |p| async move {
+                        tokio::task::spawn_blocking(move || cmd::wait(c, &p)).await?
+                    });
+                return Ok(());
+            }
+            CommandMode::Output => {
+                self.ui_event.send(Message(format!("Running {}", name)))?;
+                let out =
+                    tokio::task::spawn_blocking(move || c.stdin(Stdio::null()).output()).await??;
+                let tmp = archive::temp_dir()?.join("output");
+                fs::write(&tmp, [out.stdout, out.stderr].concat())?;
+                let re = ctx.view(std::slice::from_ref(&tmp)).await;
+                fs::remove_file(&tmp)?;
+                re?;
+            }
+        }
+        self.refresh_all().await
+    }
+
+    // run the shell in the current dir, the selected and marked files and the dirs of the
+    // groups are exported as `FFF_*` variables, all the groups are refreshed after it exits
+    pub async fn shell(&mut self) -> Void {
+        let dir = match self.current_list().dir() {
+            Some(InnerFile::Dir(d)) if d.get().protocol.is_none() => d,
+            _ => {
+                self.ui_event
+                    .send(Message("Shell can only be run in a local dir".to_string()))?;
+                return Ok(());
+            }
+        };
+
+        let list = self.current_list();
+        let marked = if list.marked().is_empty() {
+            Vec::new()
+        } else {
+            list.marked_files()
+        };
+        let mut env = vec![
+            (
+                "FFF_SELECTED".to_string(),
+                list.selected_file()
+                    .and_then(|it| local_path(&it))
+                    .unwrap_or_default(),
+            ),
+            (
+                "FFF_MARKED".to_string(),
+                marked
+                    .iter()
+                    .filter_map(|it| local_path(it))
+                    .collect::<Vec<_>>()
+                    .join("\n"),
+            ),
+            (
+                "FFF_GROUP".to_string(),
+                (self.current_group + 1).to_string(),
+            ),
+        ];
+        for (i, g) in self.groups.iter().enumerate() {
+            let dir = g.current().dir().and_then(local_path).unwrap_or_default();
+            env.push((format!("FFF_GROUP_{}", i + 1), dir));
+        }
+
+        dir.shell(&self.context, &env).await?;
+        self.refresh_all().await
+    }
+
+    // delete the marked files or the selected one, every file is confirmed before deleting
+    pub async fn delete(&mut self, permanently: bool) -> Void {
+        let files = self.current_list().marked_files();
+        if files.is_empty() {
+            self.ui_event
+                .send(Message("No file is selected".to_string()))?;
+            return Ok(());
+        }
+
+        let ctx = self.context.clone();
+        let prompt = if permanently {
+            "Delete permanently"
+        } else {
+            "Move to trash"
+        };
+        let multiple = files.len() > 1;
+        let mut all = None;
+        let mut confirmed = Vec::new();
+        for f in files {
+            let msg = format!("{} {}?", prompt, f.info().name);
+            match confirm(ctx.borrow(), &msg, multiple, &mut all).await {
+                Some(true) => confirmed.push(f),
+                Some(false) => continue,
+                None => break,
+            }
+        }
+        if confirmed.is_empty() {
+            return Ok(());
+        }
+
+        let name = format!(
+            "{} {}",
+            if permanently { "Delete" } else { "Trash" },
+            files_desc(&confirmed)
+        );
+        // the files are deleted in the blocking pool as they may be big dirs, or on other
+        // devices for the trash, the trashed local files are recorded as one operation
+        self.tasks.spawn(name.clone(), move |p| async move {
+            tokio::task::spawn_blocking(move || {
+                let rt = tokio::runtime::Handle::current();
+                p.add_total(0, confirmed.len() as u64);
+                let mut steps = Vec::new();
+                let mut re = Ok(());
+                for f in confirmed {
+                    if let Err(e) = p.check() {
+                        re = Err(e);
+                        break;
+                    }
+                    let r = match local_path(&f) {
+                        Some(path) if !permanently => {
+                            let from = PathBuf::from(path);
+                            trash::trash(&from).map(|trashed| {
+                                steps.push(Step::Trash { from, trashed });
+                            })
+                        }
+                        _ if permanently => rt.block_on(f.delete_permanently(ctx.borrow())),
+                        _ => rt.block_on(f.delete(ctx.borrow())),
+                    };
+                    if let Err(e) = r {
+                        log::error!("delete {} failed: {:?}", f.path_str(), e);
+                        re = re.and(Err(e));
+                    }
+                    p.advance(0, 1);
+                }
+                ctx.record(name, steps);
+                re
+            })
+            .await?
+        });
+        Ok(())
+    }
+
+    pub async fn open_trash(&mut self) -> Void {
+        self.open_path(trash::trash_path()?).await
+    }
+
+    fn in_trash(&self) -> Void {
+        match self.current_list().dir() {
+            Some(d) if trash::is_trash(d.info()) => Ok(()),
+            _ => {
+                self.ui_event
+                    .send(Message("Current dir is not the trash".to_string()))?;
+                Err(Error::InvalidPath(self.current().current_path()))
+            }
+        }
+    }
+
+    // restore the marked trashed items, the conflicts with the existing files at their original
+    // paths are resolved by the conflict policy, the overwritten files are moved to the trash
+    pub async fn restore_trash(&mut self) -> Void {
+        self.in_trash()?;
+        let files = self.current_list().marked_files();
+        let ctx = self.context.clone();
+        let mut all = None;
+        for f in files {
+            let to = match trash::original_path(f.info()) {
+                Ok(v) => v,
+                Err(_) => {
+                    self.ui_event
+                        .send(Message(format!("Can not restore {}", f.info().name)))?;
+                    continue;
+                }
+            };
+
+            let to = if to.symlink_metadata().is_ok() {
+                let from = trash::trashed_path(f.info())?;
+                let policy = match ctx.conflict_policy(&from, &to, &mut all).await {
+                    Some(v) => v,
+                    None => break,
+                };
+                match conflict::resolve(&from, &to, policy) {
+                    Some((to, true)) => {
+                        trash::trash(&to)?;
+                        to
+                    }
+                    Some((to, false)) => to,
+                    None => continue,
+                }
+            } else {
+                to
+            };
+
+            if let Err(e) = trash::restore(f.info(), &to) {
+                log::error!("restore {} failed: {:?}", f.path_str(), e);
+                self.ui_event
+                    .send(Message(format!("Can not restore {}", f.info().name)))?;
+            }
+        }
+        self.refresh().await
+    }
+
+    pub async fn purge_trash(&mut self) -> Void {
+        self.in_trash()?;
+        self.delete(true).await
+    }
+
+    pub async fn empty_trash(&mut self) -> Void {
+        match self.context.request_answer("Empty the trash?", false).await {
+            Some(Answer::Yes) => trash::empty()?,
+            _ => return Ok(()),
+        }
+        if self.in_trash().is_ok() {
+            self.refresh().await?;
+        }
+        Ok(())
+    }
+
+    pub fn append_clip(&mut self) -> Void {
+        let files = self.current_list().marked_files();
+        let count = self.clip.append(files);
+        self.current_list_mut().clear_mark();
+        self.ui_event.send(SetClipCount(count))?;
+        Ok(())
+    }
+
+    pub fn clear_clip(&mut self) -> Void {
+        self.clip.clear();
+        self.ui_event.send(SetClipCount(0))?;
+        Ok(())
+    }
+
+    // copy or move the files in clip to the current dir, the clip is cleared after moving
+    pub async fn paste(&mut self, moving: bool) -> Void {
+        let dir = match self.current_list().dir() {
+            Some(d) if d.info().protocol.is_none() => d.info().path.clone(),
+            _ => {
+                self.ui_event
+                    .send(Message("Can not paste to current dir".to_string()))?;
+                return Ok(());
+            }
+        };
+        if self.clip.is_empty() {
+            self.ui_event.send(Message("Clip is empty".to_string()))?;
+            return Ok(());
+        }
+
+        let ctx = self.context.clone();
+        let files = self.clip.files().clone();
+        let mut all = None;
+        let mut jobs = Vec::new();
+        for f in files {
+            let info = f.info();
+            let to = dir.join(&info.name);
+            if info.protocol.is_some() || to == info.path {
+                self.ui_event
+                    .send(Message(format!("Can not paste {}", f.path_str())))?;
+                continue;
+            }
+
+            let (to, overwrite) = if to.symlink_metadata().is_ok() {
+                let policy = match ctx.conflict_policy(&info.path, &to, &mut all).await {
+                    Some(v) => v,
+                    None => break,
+                };
+                match conflict::resolve(&info.path, &to, policy) {
+                    Some(v) => v,
+                    None => continue,
+                }
+            } else {
+                (to, false)
+            };
+            jobs.push((info.path.clone(), to, overwrite));
+        }
+
+        if moving {
+            self.clear_clip()?;
+        }
+        if jobs.is_empty() {
+            return Ok(());
+        }
+
+        let name = match jobs.as_slice() {
+            [(from, _, _)] => from.display().to_string(),
+            js => format!("{} files", js.len()),
+        };
+        let name = format!("{} {}", if moving { "Move" } else { "Copy" }, name);
+        let ctx = self.context.clone();
+        let desc = name.clone();
+        self.tasks.spawn(name, move |p| async move {
+            let (steps, re) = tokio::task::spawn_blocking(move || {
+                let mut steps = Vec::new();
+                let re = paste_all(jobs, moving, &p, &mut steps);
+                (steps, re)
+            })
+            .await?;
+            ctx.record(desc, steps);
+            re
+        });
+        Ok(())
+    }
+
+    // rename the marked files by editing their names in the editor, one name a line, the
+    // renames are previewed before done, and edited again if there are conflicts
+    pub async fn bulk_rename(&mut self) -> Void {
+        let files = self.current_list().marked_files();
+        if files.is_empty() || files.iter().any(|it| it.info().protocol.is_some()) {
+            self.ui_event
+                .send(Message("Can not rename these files".to_string()))?;
+            return Ok(());
+        }
+        let mut names: Vec<_> = files.iter().map(|it| it.info().name.clone()).collect();
+        if names.iter().any(|it| it.contains('\n')) {
+            self.ui_event.send(Message(
+                "Can not rename a file with new line in name".to_string(),
+            ))?;
+            return Ok(());
+        }
+
+        let ctx = self.context.clone();
+        let tmp = archive::temp_dir()?.join("rename");
+        let (moves, count) = loop {
+            fs::write(&tmp, names.join("\n") + "\n")?;
+            let status = ctx.edit(std::slice::from_ref(&tmp)).await;
+            let edited = fs::read_to_string(&tmp);
+            fs::remove_file(&tmp)?;
+            if !status?.success() {
+                self.ui_event
+                    .send(Message("Editor failed, nothing is renamed".to_string()))?;
+                return Ok(());
+            }
+
+            let mut edited: Vec<_> = edited?.lines().map(|it| it.to_string()).collect();
+            while edited.len() > names.len() && edited.last().map(String::as_str) == Some("") {
+                edited.pop();
+            }
+            if edited.len() != names.len() {
+                self.ui_event.send(Message(format!(
+                    "{} names are expected, nothing is renamed",
+                    names.len()
+                )))?;
+                return Ok(());
+            }
+            names = edited;
+
+            let mut pairs = Vec::new();
+            let mut conflicts = Vec::new();
+            for (f, name) in files.iter().zip(names.iter()) {
+                match rename::target(&f.info().path, name) {
+                    Ok(t) => pairs.push((f.info().path.clone(), t)),
+                    Err(e) => conflicts.push((f.info().path.clone(), e)),
+                }
+            }
+            let lines = pairs
+                .iter()
+                .filter(|(f, t)| f != t)
+                .map(|(f, t)| format!("{} -> {}", file_name(f), file_name(t)))
+                .collect::<Vec<_>>();
+            let (moves, cs) = rename::plan(pairs);
+            conflicts.extend(cs);
+            if conflicts.is_empty() {
+                if moves.is_empty() {
+                    return Ok(());
+                }
+                let count = lines.len();
+                self.ui_event.send(SetPreview(Some(lines)))?;
+                let msg = format!("Rename {} files?", count);
+                let answer = ctx.request_answer(&msg, false).await;
+                self.ui_event.send(SetPreview(None))?;
+                match answer {
+                    Some(Answer::Yes) => break (moves, count),
+                    _ => return Ok(()),
+                }
+            }
+
+            let lines = conflicts.iter().map(|(f, e)| conflict_line(f, e)).collect();
+            self.ui_event.send(SetPreview(Some(lines)))?;
+            let msg = format!("{} conflicts, edit again?", conflicts.len());
+            let answer = ctx.request_answer(&msg, false).await;
+            self.ui_event.send(SetPreview(None))?;
+            match answer {
+                Some(Answer::Yes) => continue,
+                _ => return Ok(()),
+            }
+        };
+
+        let mut steps = Vec::new();
+        let failed = rename::apply(moves, &mut steps);
+        ctx.record(format!("Bulk rename {} files", count), steps);
+        self.refresh().await?;
+        self.report_failed(&failed)
+    }
+
+    // rename the marked files by a pattern, see `pattern` for its forms, the renames are
+    // previewed while the pattern is typed, and the conflicting ones are skipped
+    pub async fn pattern_rename(&mut self) -> Void {
+        let files = self.current_list().marked_files();
+        if files.is_empty() || files.iter().any(|it| it.info().protocol.is_some()) {
+            self.ui_event
+                .send(Message("Can not rename these files".to_string()))?;
+            return Ok(());
+        }
+        let files: Vec<_> = files
+            .iter()
+            .map(|it| (it.info().path.clone(), it.info().modified))
+            .collect();
+
+        let ctx = self.context.clone();
+        let ui_event = self.ui_event.clone();
+        let live = files.clone();
+        let input = ctx
+            .request_live_input(
+                "Pattern",
+                Box::new(move |text| {
+                    let lines = match Pattern::parse(text) {
+                        _ if text.is_empty() => None,
+                        Ok(p) => Some(rename::pattern_plan(&p, &live).1),
+                        Err(e) => Some(vec![error_desc(&e)]),
+                    };
+                    ui_event.send(SetPreview(lines)).unwrap();
+                }),
+            )
+            .await;
+        let pattern = match input.as_deref().map(Pattern::parse) {
+            Some(Ok(p)) => p,
+            re => {
+                self.ui_event.send(SetPreview(None))?;
+                if let Some(Err(e)) = re {
+                    self.ui_event.send(Message(error_desc(&e)))?;
+                }
+                return Ok(());
+            }
+        };
+
+        let (moves, lines, count, conflicts) = rename::pattern_plan(&pattern, &files);
+        if count == 0 {
+            self.ui_event.send(SetPreview(None))?;
+            self.ui_event
+                .send(Message("Nothing to rename".to_string()))?;
+            return Ok(());
+        }
+        self.ui_event.send(SetPreview(Some(lines)))?;
+        let msg = match conflicts {
+            0 => format!("Rename {} files?", count),
+            c => format!("Rename {} files, skip {} conflicts?", count, c),
+        };
+        let answer = ctx.request_answer(&msg, false).await;
+        self.ui_event.send(SetPreview(None))?;
+        match answer {
+            Some(Answer::Yes) => {}
+            _ => return Ok(()),
+        }
+
+        let mut steps = Vec::new();
+        let failed = rename::apply(moves, &mut steps);
+        ctx.record(format!("Pattern rename {} files", count), steps);
+        self.refresh().await?;
+        self.report_failed(&failed)
+    }
+
+    fn report_failed(&self, failed: &[(PathBuf, Error)]) -> Void {
+        if let Some(m) = conflict::failure_message(failed) {
+            self.ui_event.send(Message(m))?;
+        }
+        Ok(())
+    }
+
+    // change the mode, the owner or the times of the marked files, previewed while typed,
+    // the contents of the dirs are changed too if confirmed
+    pub async fn change_attribute(&mut self, attr: Attribute) -> Void {
+        let files = self.current_list().marked_files();
+        if files.is_empty() || files.iter().any(|it| it.info().protocol.is_some()) {
+            self.ui_event.send(Message(format!(
+                "Can not change {} of these files",
+                attr.name()
+            )))?;
+            return Ok(());
+        }
+        let infos: Vec<_> = files.iter().map(|it| it.info().clone()).collect();
+
+        let ctx = self.context.clone();
+        let ui_event = self.ui_event.clone();
+        let live = infos.clone();
+        let input = ctx
+            .request_live_input(
+                attr.prompt(),
+                Box::new(move |text| {
+                    let lines = attr.preview(text, &live);
+                    ui_event.send(SetPreview(lines)).unwrap();
+                }),
+            )
+            .await;
+        self.ui_event.send(SetPreview(None))?;
+        let change = match input.as_deref().map(|it| attr.parse(it)) {
+            Some(Ok(c)) => c,
+            Some(Err(e)) => {
+                self.ui_event.send(Message(error_desc(&e)))?;
+                return Ok(());
+            }
+            None => return Ok(()),
+        };
+
+        let recursive = if infos.iter().any(|it| it.is_dir && it.link.is_none()) {
+            match ctx
+                .request_answer("Change the files in the dirs too?", false)
+                .await
+            {
+                Some(Answer::Yes) => true,
+                Some(_) => false,
+                None => return Ok(()),
+            }
+        } else {
+            false
+        };
+
+        let paths: Vec<_> = infos.into_iter().map(|it| it.path).collect();
+        let name = format!("Change {} of {}", attr.name(), files_desc(&files));
+        let ui_event = self.ui_event.clone();
+        self.tasks.spawn(name, move |p| async move {
+            let failed = attr::change_all(paths, change, recursive, p).await?;
+            if let Some(m) = conflict::failure_message(&failed) {
+                ui_event.send(Message(m))?;
+            }
+            Ok(())
+        });
+        Ok(())
+    }
+
+    // link the marked files into the current dir of the group switched from, the relative
+    // links are relative to that dir
+    pub async fn link(&mut self, kind: LinkKind) -> Void {
+        let dir = match self.groups[self.last_group].current().dir() {
+            Some(d) if self.last_group != self.current_group && d.info().protocol.is_none() => {
+                d.info().path.clone()
+            }
+            _ => {
+                self.ui_event.send(Message(
+                    "Switch from the group to link into first".to_string(),
+                ))?;
+                return Ok(());
+            }
+        };
+        let files = self.current_list().marked_files();
+        if files.is_empty() || files.iter().any(|it| it.info().protocol.is_some()) {
+            self.ui_event
+                .send(Message("Can not link these files".to_string()))?;
+            return Ok(());
+        }
+
+        let ctx = self.context.clone();
+        let mut all = None;
+        let mut steps = Vec::new();
+        let mut failed = Vec::new();
+        for f in files.iter() {
+            let from = &f.info().path;
+            let to = dir.join(&f.info().name);
+            let (to, overwrite) = if to.symlink_metadata().is_ok() {
+                let policy = match ctx.conflict_policy(from, &to, &mut all).await {
+                    Some(v) => v,
+                    None => break,
+                };
+                match conflict::resolve(from, &to, policy) {
+                    Some(v) => v,
+                    None => continue,
+                }
+            } else {
+                (to, false)
+            };
+
+            let hard = kind == LinkKind::Hard;
+            let re = link_target(from, &to, kind).and_then(|target| {
+                if overwrite {
+                    let trashed = trash::trash(&to)?;
+                    steps.push(Step::Trash {
+                        from: to.clone(),
+                        trashed,
+                    });
+                }
+                link(&target, &to, hard)?;
+                steps.push(if hard {
+                    Step::HardLink {
+                        from: target,
+                        to: to.clone(),
+                    }
+                } else {
+                    Step::Symlink {
+                        target,
+                        path: to.clone(),
+                    }
+                });
+                Ok(())
+            });
+            if let Err(e) = re {
+                log::error!("link {} failed: {:?}", to.display(), e);
+                failed.push((to, e));
+            }
+        }
+
+        if !steps.is_empty() {
+            let desc = format!("Link {} into {}", files_desc(&files), dir.display());
+            ctx.record(desc, steps);
+        }
+        if let Some((f, e)) = failed.first() {
+            self.ui_event.send(Message(format!(
+                "{} links failed, {}",
+                failed.len(),
+                conflict_line(f, e)
+            )))?;
+        }
+        self.refresh_all().await
+    }
+
+    // pack the marked files into an archive in the current dir, the format is chosen by the
+    // extension of the name
+    pub async fn pack(&mut self) -> Void {
+        let dir = match self.current_list().dir() {
+            Some(d) if d.info().protocol.is_none() => d.info().path.clone(),
+            _ => {
+                self.ui_event
+                    .send(Message("Can not pack to current dir".to_string()))?;
+                return Ok(());
+            }
+        };
+        let files = self.current_list().marked_files();
+        if files.is_empty() || files.iter().any(|it| it.info().protocol.is_some()) {
+            self.ui_event
+                .send(Message("Can not pack these files".to_string()))?;
+            return Ok(());
+        }
+
+        let ctx = self.context.clone();
+        let msg = format!("Archive name ({})", pack::CREATE_EXTENSIONS.join(", "));
+        let name = match ctx.request_input(&msg).await {
+            Some(v) => v.trim().to_string(),
+            None => return Ok(()),
+        };
+        if !pack::can_create(&name) || name.contains('/') {
+            self.ui_event
+                .send(Message(format!("Can not create archive {}", name)))?;
+            return Ok(());
+        }
+        let paths: Vec<_> = files.iter().map(|it| it.info().path.clone()).collect();
+        let to = dir.join(&name);
+        let (to, overwrite) = if to.symlink_metadata().is_ok() {
+            // the archive is compared with the newest of the packed files
+            let ps = paths.clone();
+            let newest = tokio::task::spawn_blocking(move || pack::newest(&ps)).await??;
+            let policy = match ctx.conflict_policy(&newest, &to, &mut None).await {
+                Some(v) => v,
+                None => return Ok(()),
+            };
+            match conflict::resolve(&newest, &to, policy) {
+                // the new name keeps the extension of the archive
+                Some((t, _)) if t != to => (pack::unique_path(&to), false),
+                Some(v) => v,
+                None => return Ok(()),
+            }
+        } else {
+            (to, false)
+        };
+
+        let name = format!("Pack {} into {}", files_desc(&files), name);
+        self.tasks.spawn(name, move |p| async move {
+            tokio::task::spawn_blocking(move || {
+                // the old archive goes to the trash, so it is not lost if the packing fails
+                if overwrite {
+                    trash::trash(&to)?;
+                }
+                pack::pack(&paths, &to, &p)
+            })
+            .await?
+        });
+        Ok(())
+    }
+
+    // extract the selected archive next to it in background, an existing target is resolved by
+    // the conflict policy
+    pub async fn extract(&mut self) -> Void {
+        let file = match self.current_list().selected_file() {
+            Some(f) if f.is_file() && f.info().protocol.is_none() => f,
+            _ => {
+                self.ui_event
+                    .send(Message("Can not extract this file".to_string()))?;
+                return Ok(());
+            }
+        };
+        let info = file.info();
+        if !pack::can_extract(&info.name) {
+            self.ui_event
+                .send(Message(format!("{} is not an archive", info.name)))?;
+            return Ok(());
+        }
+
+        let path = info.path.clone();
+        let name = format!("Extract {}", info.name);
+        let p = path.clone();
+        let to = tokio::task::spawn_blocking(move || pack::target(&p)).await??;
+        let (to, overwrite) = if to.symlink_metadata().is_ok() {
+            let ctx = self.context.clone();
+            let policy = match ctx.conflict_policy(&path, &to, &mut None).await {
+                Some(v) => v,
+                None => return Ok(()),
+            };
+            match conflict::resolve(&path, &to, policy) {
+                Some(v) => v,
+                None => return Ok(()),
+            }
+        } else {
+            (to, false)
+        };
+
+        let ui_event = self.ui_event.clone();
+        self.tasks.spawn(name, move |p| async move {
+            let t = to.clone();
+            tokio::task::spawn_blocking(move || {
+                if overwrite {
+                    trash::trash(&t)?;
+                }
+                pack::unpack(&path, &t, p)
+            })
+            .await??;
+            ui_event.send(Message(format!("Extracted to {}", to.display())))?;
+            Ok(())
+        });
+        Ok(())
+    }
+
+    // compute the sizes of the marked dirs, or of all the dirs in the list, in background,
+    // the list is sorted again after each dir
+    pub fn dir_size(&mut self, all: bool) -> Void {
+        let list = self.current_list();
+        let files = if all {
+            list.files()
+        } else {
+            list.marked_files()
+        };
+        let dirs: Vec<_> = files
+            .iter()
+            .filter(|it| dir_size::computable(it.info()))
+            .collect();
+        if dirs.is_empty() {
+            self.ui_event
+                .send(Message("No dirs to compute".to_string()))?;
+            return Ok(());
+        }
+        let paths: Vec<_> = dirs
+            .iter()
+            .filter(|it| dir_size::cached(it.info()).is_none())
+            .map(|it| it.info().path.clone())
+            .collect();
+        if paths.is_empty() {
+            self.current_list_mut().resort();
+            return Ok(());
+        }
+
+        let name = format!("Compute size of {} dirs", paths.len());
+        let ctx = self.context.clone();
+        self.tasks
+            .spawn(name, move |p| dir_size::compute_all(paths, ctx, p));
+        Ok(())
+    }
+
+    // show the computed dir sizes, the list is sorted again
+    pub fn dir_size_computed(&mut self) -> Void {
+        dir_size::clear_pending();
+        self.current_list_mut().resort();
+        Ok(())
+    }
+
+    // undo the last operation in the journal, or redo the last undone one
+    pub fn undo(&mut self, redo: bool) -> Void {
+        let ctx = self.context.clone();
+        let entry = match ctx.journal().take(redo) {
+            Ok(Some(v)) => v,
+            Ok(None) => {
+                let action = if redo { "redo" } else { "undo" };
+                self.ui_event
+                    .send(Message(format!("Nothing to {}", action)))?;
+                return Ok(());
+            }
+            Err(e) => {
+                self.ui_event.send(Message(journal::refusal(redo, &e)))?;
+                return Ok(());
+            }
+        };
+
+        let name = format!("{} {}", if redo { "Redo" } else { "Undo" }, entry.desc);
+        self.tasks.spawn(name, move |p| async move {
+            let (entry, re) = entry.replay_blocking(redo, p).await?;
+            ctx.journal().put_back(entry, redo);
+            re
+        });
+        Ok(())
+    }
+
+    pub fn fake_task(&mut self) -> Void {
+        self.tasks.spawn("Fake task".to_string(), |p| async move {
+            p.add_total(0, FAKE_TASK_STEPS);
+            for _ in 0..FAKE_TASK_STEPS {
+                p.check()?;
+                tokio::time::delay_for(Duration::from_millis(500)).await;
+                p.advance(0, 1);
+            }
+            Ok(())
+        });
+        Ok(())
+    }
+
+    // the task bindings are active while the task detail is shown
+    pub fn show_task_detail(&mut self, show: bool) -> Void {
+        self.kbd
+            .set_normal_extra(if show { Some(BindingType::Task) } else { None });
+        self.ui_event.send(ShowTaskDetail(show))?;
+        Ok(())
+    }
+
+    // cancel the tasks by the ids shown in the task detail, until the input is aborted
+    pub async fn cancel_task(&mut self, once: bool) -> Void {
+        while !self.tasks.is_empty() {
+            let id = match self.context.request_input("Cancel task").await {
+                Some(v) => v,
+                None => break,
+            };
+            let cancelled = matches!(id.trim().parse(), Ok(it) if self.tasks.cancel(it));
+            if !cancelled {
+                self.ui_event
+                    .send(Message(format!("No task {}", id.trim())))?;
+            }
+            if once {
+                break;
+            }
+        }
+        Ok(())
+    }
+
     fn bind_list(sender: &UIEventSender, list: &mut FileList) {
         let s1 = sender.clone();
         list.subscribe_file_change(move |fs| {
@@ -0,0 +1,198 @@
+use crate::kbd::action::TASK_DONE_ACTION;
+use crate::kbd::Kbd;
+use crate::model::file::readable_size;
+use crate::model::result::{Error, Void};
+use crate::ui::event::UIEvent::{Message, SetTasks};
+use crate::ui::event::UIEventSender;
+use std::future::Future;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+const TICK: Duration = Duration::from_millis(500);
+
+// the progress of a task in bytes and items, it is also used to cancel the task
+pub struct Progress {
+    cancelled: AtomicBool,
+    total_bytes: AtomicU64,
+    done_bytes: AtomicU64,
+    total_items: AtomicU64,
+    done_items: AtomicU64,
+}
+
+impl Progress {
+    pub fn new() -> Self {
+        Progress {
+            cancelled: AtomicBool::new(false),
+            total_bytes: AtomicU64::new(0),
+            done_bytes: AtomicU64::new(0),
+            total_items: AtomicU64::new(0),
+            done_items: AtomicU64::new(0),
+        }
+    }
+
+    pub fn cancel(&self) {
+        self.cancelled.store(true, Ordering::Relaxed);
+    }
+
+    // the long running operations should call it often to stop as soon as cancelled
+    pub fn check(&self) -> Void {
+        if self.cancelled.load(Ordering::Relaxed) {
+            return Err(Error::Cancelled);
+        }
+        Ok(())
+    }
+
+    pub fn add_total(&self, bytes: u64, items: u64) {
+        self.total_bytes.fetch_add(bytes, Ordering::Relaxed);
+        self.total_items.fetch_add(items, Ordering::Relaxed);
+    }
+
+    pub fn advance(&self, bytes: u64, items: u64) {
+        self.done_bytes.fetch_add(bytes, Ordering::Relaxed);
+        self.done_items.fetch_add(items, Ordering::Relaxed);
+    }
+
+    fn desc(&self) -> String {
+        let items = format!(
+            "{}/{}",
+            self.done_items.load(Ordering::Relaxed),
+            self.total_items.load(Ordering::Relaxed)
+        );
+        let total = self.total_bytes.load(Ordering::Relaxed);
+        if total == 0 {
+            return items;
+        }
+
+        let done = self.done_bytes.load(Ordering::Relaxed);
+        format!(
+            "{} {}/{} {}%",
+            items,
+            readable_size(done),
+            readable_size(total),
+            done * 100 / total
+        )
+    }
+}
+
+struct Task {
+    id: usize,
+    name: String,
+    progress: Arc<Progress>,
+}
+
+struct Tasks {
+    list: Vec<Task>,
+    // whether the progress is being sent to ui periodically
+    ticking: bool,
+}
+
+pub struct TaskManager {
+    tasks: Arc<Mutex<Tasks>>,
+    next_id: usize,
+    ui_event: UIEventSender,
+    kbd: Arc<Kbd>,
+}
+
+impl TaskManager {
+    pub fn new(ui_event: UIEventSender, kbd: Arc<Kbd>) -> Self {
+        TaskManager {
+            tasks: Arc::new(Mutex::new(Tasks {
+                list: Vec::new(),
+                ticking: false,
+            })),
+            next_id: 1,
+            ui_event,
+            kbd,
+        }
+    }
+
+    // run the task in background, the action loop is notified by `TASK_DONE_ACTION` when it ends
+    pub fn spawn<F, Fut>(&mut self, name: String, f: F)
+    where
+        F: FnOnce(Arc<Progress>) -> Fut,
+        Fut: Future<Output = Void> + Send + 'static,
+    {
+        let id = self.next_id;
+        self.next_id += 1;
+        let progress = Arc::new(Progress::new());
+        let fut = f(progress.clone());
+
+        {
+            let mut ts = self.tasks.lock().unwrap();
+            ts.list.push(Task {
+                id,
+                name: name.clone(),
+                progress,
+            });
+            if !ts.ticking {
+                ts.ticking = true;
+                self.tick();
+            }
+        }
+        sync(&self.tasks, &self.ui_event);
+
+        let tasks = self.tasks.clone();
+        let ui_event = self.ui_event.clone();
+        let kbd = self.kbd.clone();
+        tokio::spawn(async move {
+            let re = fut.await;
+            tasks.lock().unwrap().list.retain(|it| it.id != id);
+            sync(&tasks, &ui_event);
+
+            match re {
+                Ok(_) | Err(Error::Cancelled) => {}
+                Err(e) => {
+                    log::error!("task {} failed: {:?}", name, e);
+                    ui_event.send(Message(format!("{} failed", name))).unwrap();
+                }
+            }
+            kbd.send_action(TASK_DONE_ACTION);
+        });
+    }
+
+    // cancel the task by its id, which is shown in the task detail
+    pub fn cancel(&self, id: usize) -> bool {
+        let ts = self.tasks.lock().unwrap();
+        match ts.list.iter().find(|it| it.id == id) {
+            Some(t) => {
+                t.progress.cancel();
+                true
+            }
+            None => false,
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.tasks.lock().unwrap().list.is_empty()
+    }
+
+    fn tick(&self) {
+        let tasks = self.tasks.clone();
+        let ui_event = self.ui_event.clone();
+        tokio::spawn(async move {
+            loop {
+                tokio::time::delay_for(TICK).await;
+                {
+                    let mut ts = tasks.lock().unwrap();
+                    if ts.list.is_empty() {
+                        ts.ticking = false;
+                        break;
+                    }
+                }
+                sync(&tasks, &ui_event);
+            }
+        });
+    }
+}
+
+fn sync(tasks: &Arc<Mutex<Tasks>>, ui_event: &UIEventSender) {
+    let descs = tasks
+        .lock()
+        .unwrap()
+        .list
+        .iter()
+        .map(|it| format!("[{}] {} {}", it.id, it.name, it.progress.desc()))
+        .collect();
+    ui_event.send(SetTasks(descs)).unwrap();
+}
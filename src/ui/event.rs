@@ -1,3 +1,4 @@
+use crate::model::file::file_mode::mode_string;
 use crate::model::file::InnerFile;
 use crossbeam_channel::{bounded, Receiver, SendError, Sender};
 
@@ -5,20 +6,34 @@ use crossbeam_channel::{bounded, Receiver, SendError, Sender};
 pub struct FileItem {
     pub name: String,
     pub modify_time: String,
+    pub change_time: String,
+    pub inode: String,
     pub mode_str: String,
+    pub links: String,
+    pub owner: String,
+    pub group: String,
     pub size: String,
     pub is_dir: bool,
+    // the target of the symlink, and whether it is broken
+    pub link: Option<(String, bool)>,
 }
 
 impl From<&InnerFile> for FileItem {
     fn from(f: &InnerFile) -> Self {
         let info = f.info();
+        let stat = info.stat.as_ref();
         FileItem {
             name: info.name.clone(),
             modify_time: f.modify_time_str(),
-            mode_str: info.mode.clone(),
+            change_time: f.change_time_str(),
+            inode: stat.map_or("-".to_string(), |it| it.inode.to_string()),
+            mode_str: mode_string(info.mode),
+            links: stat.map_or("-".to_string(), |it| it.nlink.to_string()),
+            owner: stat.map_or("-".to_string(), |it| it.owner.clone()),
+            group: stat.map_or("-".to_string(), |it| it.group.clone()),
             size: f.readable_size(),
             is_dir: f.is_dir(),
+            link: info.link.as_ref().map(|it| (it.target.clone(), it.broken)),
         }
     }
 }
@@ -45,10 +60,19 @@ pub enum UIEvent {
     SetShowDetail(bool),
 
     SetBookmark(Vec<String>),
+    SetClipCount(usize),
+    SetTasks(Vec<String>),
+    ShowTaskDetail(bool),
+    // the lines to preview in the board before a change is confirmed, None to close it
+    SetPreview(Option<Vec<String>>),
     UpdateFileItem(Vec<FileItem>),
     ShowKeyNav(Vec<(String, String)>),
     ClearKeyNav,
 
+    // an external program owns the terminal between them
+    Suspend,
+    Resume,
+
     InputEnter(String),
     InputQuit,
     InputUpdate(String, usize),
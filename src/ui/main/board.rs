@@ -11,14 +11,21 @@ use crate::ui::{Mrc, ToMrc};
 
 pub struct Board {
     main: Flex,
+    items: Mrc<Flex>,
     column: Mrc<FileColumn>,
     bookmark: Mrc<Bookmark>,
+    task: Mrc<Bookmark>,
+    preview: Mrc<Bookmark>,
+    show_task: bool,
+    show_preview: bool,
     line: Mrc<CornerLine>,
 }
 
 impl Board {
     pub fn new() -> Self {
         let bookmark = Bookmark::new("BOOKMARKS".to_string()).mrc();
+        let task = Bookmark::new("TASKS".to_string()).mrc();
+        let preview = Bookmark::new("PREVIEW".to_string()).mrc();
         let line = CornerLine::new('║', '╥', '─').mrc();
         let column = FileColumn::new().mrc();
         let items = Flex::row()
@@ -34,12 +41,54 @@ impl Board {
                 it.add(SizedBox::new(Line::new(false).mrc()).max_width().mrc());
                 it.add_flex(items.clone(), 1);
             }),
+            items,
             column,
             bookmark,
+            task,
+            preview,
+            show_task: false,
+            show_preview: false,
             line,
         }
     }
 
+    pub fn set_tasks(&mut self, ts: Vec<String>) {
+        self.task.borrow_mut().reset_items(ts);
+        self.redraw();
+    }
+
+    // the task detail is shown in place of the bookmarks
+    pub fn set_show_task(&mut self, show: bool) {
+        self.show_task = show;
+        self.reset_left();
+    }
+
+    // the preview is shown in place of the bookmarks and the task detail
+    pub fn set_preview(&mut self, lines: Option<Vec<String>>) {
+        self.show_preview = lines.is_some();
+        self.preview
+            .borrow_mut()
+            .reset_items(lines.unwrap_or_default());
+        self.reset_left();
+    }
+
+    fn reset_left(&mut self) {
+        let left: Mrc<Bookmark> = if self.show_preview {
+            self.preview.clone()
+        } else if self.show_task {
+            self.task.clone()
+        } else {
+            self.bookmark.clone()
+        };
+        self.items.borrow_mut().also(|it| {
+            it.empty_it();
+            it.add(left);
+            it.add(SizedBox::new(self.line.clone()).max_height().mrc());
+            it.add(self.column.clone());
+        });
+        self.redraw();
+    }
+
     pub fn set_bookmark(&mut self, bs: Vec<String>) {
         self.bookmark.borrow_mut().reset_items(bs);
         self.redraw();
@@ -5,27 +5,56 @@ use crossbeam_channel::{Receiver, Sender};
 
 pub fn handle(mut ui: UI, rx: Receiver<EventBody>) {
     let mut in_queue = false;
+    // the events received while an external program owns the terminal, handled after resumed
+    let mut suspended = false;
+    let mut pending = Vec::new();
     while let Ok(ev) = rx.recv() {
         log::debug!("handle {:?}  {}", ev, in_queue);
 
         match ev {
-            EventBody::Single(data, tx) => match data {
-                UIEvent::StartQueue => {
-                    in_queue = true;
-                }
-                UIEvent::EndQueue => {
-                    in_queue = false;
-                    ui.flush();
-                }
-                d => {
-                    handle_single(&mut ui, d);
-                    after_handle(&mut ui, tx, &in_queue);
+            EventBody::Single(UIEvent::Suspend, tx) if !suspended => {
+                suspended = true;
+                ui.suspend();
+                done(tx);
+            }
+            EventBody::Single(UIEvent::Resume, tx) if suspended => {
+                suspended = false;
+                ui.resume();
+                for ev in pending.drain(..) {
+                    handle_body(&mut ui, ev, &mut in_queue);
                 }
-            },
-            EventBody::Batch(data, tx) => {
-                data.into_iter().for_each(|it| handle_single(&mut ui, it));
-                after_handle(&mut ui, tx, &in_queue)
+                done(tx);
             }
+            ev if suspended => pending.push(ev),
+            ev => handle_body(&mut ui, ev, &mut in_queue),
+        }
+    }
+}
+
+fn done(tx: Option<Sender<bool>>) {
+    if let Some(t) = tx {
+        t.send(true).unwrap();
+    }
+}
+
+fn handle_body(ui: &mut UI, ev: EventBody, in_queue: &mut bool) {
+    match ev {
+        EventBody::Single(data, tx) => match data {
+            UIEvent::StartQueue => {
+                *in_queue = true;
+            }
+            UIEvent::EndQueue => {
+                *in_queue = false;
+                ui.flush();
+            }
+            d => {
+                handle_single(ui, d);
+                after_handle(ui, tx, in_queue);
+            }
+        },
+        EventBody::Batch(data, tx) => {
+            data.into_iter().for_each(|it| handle_single(ui, it));
+            after_handle(ui, tx, in_queue)
         }
     }
 }
@@ -35,6 +64,14 @@ fn handle_single(ui: &mut UI, ev: UIEvent) {
         SwitchTab(idx) => ui.switch_tab(idx),
         StartLoading => ui.start_loading(),
         SetBookmark(bs) => ui.board_mut().set_bookmark(bs),
+        SetClipCount(c) => ui.statusbar_mut().set_clip_count(c),
+        SetTasks(ts) => {
+            ui.statusbar_mut().set_task_count(ts.len());
+            ui.board_mut().set_tasks(ts);
+        }
+        ShowTaskDetail(show) => ui.board_mut().set_show_task(show),
+        SetPreview(ls) => ui.board_mut().set_preview(ls),
+        Message(m) => ui.show_message(m),
         SetPath(p) => ui.path_mut().set_path(&p),
         InitColumn(fs) => ui.board_mut().init_files(fs),
         InitSelect(ss) => ui.board_mut().init_selected(ss),
@@ -23,6 +23,28 @@ impl InverseColor for Colors {
     }
 }
 
+// the widths of the detail columns of a list, so they are aligned
+#[derive(Clone, Copy, Default)]
+pub struct Widths {
+    inode: usize,
+    links: usize,
+    owner: usize,
+    group: usize,
+    size: usize,
+}
+
+impl Widths {
+    pub fn of(items: &[FileItem]) -> Self {
+        items.iter().fold(Widths::default(), |w, it| Widths {
+            inode: w.inode.max(it.inode.len()),
+            links: w.links.max(it.links.len()),
+            owner: w.owner.max(it.owner.len()),
+            group: w.group.max(it.group.len()),
+            size: w.size.max(it.size.len()),
+        })
+    }
+}
+
 pub struct FileLabel {
     labels: Vec<Mrc<Label>>,
     selected: bool,
@@ -33,18 +55,20 @@ pub struct FileLabel {
     background: Background,
     pub item: FileItem,
     pub show_detail: bool,
-    max: usize,
+    widths: Widths,
 }
 
 impl FileLabel {
-    pub fn new(item: FileItem, max: usize, show_detail: bool) -> Self {
-        let c = if item.is_dir {
+    pub fn new(item: FileItem, widths: Widths, show_detail: bool) -> Self {
+        let c = if let Some((_, true)) = item.link {
+            Colors::new(Color::Red, Color::Black)
+        } else if item.is_dir {
             Colors::new(Color::Cyan, Color::Black)
         } else {
             Colors::new(Color::White, Color::Black)
         };
 
-        let (labels, body) = FileLabel::create_body(show_detail, max, &item, &c);
+        let (labels, body) = FileLabel::create_body(show_detail, widths, &item, &c);
         FileLabel {
             selected: false,
             marked: false,
@@ -55,7 +79,7 @@ impl FileLabel {
             item,
             labels,
             show_detail,
-            max,
+            widths,
         }
     }
 
@@ -85,7 +109,7 @@ impl FileLabel {
         }
 
         self.show_detail = show;
-        let (labels, flex) = FileLabel::create_body(show, self.max, &self.item, &self.color);
+        let (labels, flex) = FileLabel::create_body(show, self.widths, &self.item, &self.color);
         self.labels = labels;
         self.background.set_child(flex);
         self.ensure_color();
@@ -111,7 +135,7 @@ impl FileLabel {
 
     fn create_body(
         show_detail: bool,
-        max: usize,
+        w: Widths,
         item: &FileItem,
         c: &Colors,
     ) -> (Vec<Mrc<Label>>, Mrc<Flex>) {
@@ -132,10 +156,28 @@ impl FileLabel {
                 it.add(Space::new_with_width(2).mrc());
             })
         } else {
+            let target = item
+                .link
+                .as_ref()
+                .map_or("".to_string(), |(t, _)| format!(" -> {}", t));
             flex.apply(|it| {
                 let l1 = Label::from(format!(
-                    "{0}  {1}  {2:>3$}  {4}",
-                    &item.modify_time, &item.mode_str, &item.size, max, &item.name
+                    "{:>iw$}  {}  {}  {} {:>lw$} {:<ow$} {:<gw$}  {:>sw$}  {}{}",
+                    &item.inode,
+                    &item.modify_time,
+                    &item.change_time,
+                    &item.mode_str,
+                    &item.links,
+                    &item.owner,
+                    &item.group,
+                    &item.size,
+                    &item.name,
+                    target,
+                    iw = w.inode,
+                    lw = w.links,
+                    ow = w.owner,
+                    gw = w.group,
+                    sw = w.size,
                 ))
                 .mrc();
 
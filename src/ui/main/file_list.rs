@@ -6,7 +6,7 @@ use crate::ui::layout::container::UseMin;
 use crate::ui::layout::flex::Flex;
 use crate::ui::layout::space::Space;
 use crate::ui::main::corner_line::CornerLine;
-use crate::ui::main::file_label::FileLabel;
+use crate::ui::main::file_label::{FileLabel, Widths};
 use crate::ui::widget::label::Label;
 use crate::ui::{InnerFunctional, Mrc, ToMrc};
 
@@ -54,16 +54,15 @@ impl FileList {
     }
 
     pub fn set_files(&mut self, list: Vec<FileItem>) {
-        let max = list
-            .iter()
-            .fold(0usize, |acc, it| std::cmp::max(acc, it.size.len()));
+        let widths = Widths::of(&list);
         let files: Vec<_> = list
             .into_iter()
-            .map(|it| FileLabel::new(it, max, self.show_detail).mrc())
+            .map(|it| FileLabel::new(it, widths, self.show_detail).mrc())
             .collect();
+        // the old indexes may be out of the new files
+        self.marked = Vec::new();
+        self.select_index = None;
         self.files = files;
-        self.set_marked(Vec::new());
-        self.set_selected(None);
 
         self.redraw();
     }
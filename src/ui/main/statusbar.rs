@@ -3,6 +3,7 @@ use crate::ui::base::draw::Draw;
 use crate::ui::layout::background::Background;
 use crate::ui::layout::flex::Flex;
 use crate::ui::layout::sized::SizedBox;
+use crate::ui::layout::space::Space;
 use crate::ui::widget::label::Label;
 use crate::ui::widget::spinner::Spinner;
 use crate::ui::{Mrc, ToMrc};
@@ -10,7 +11,12 @@ use crossterm::style::{Color, Colors};
 
 pub struct Statusbar {
     spinner: Mrc<Spinner>,
+    clip: Mrc<Label>,
+    task: Mrc<Label>,
     main: Background,
+    loading: bool,
+    task_count: usize,
+    suspended: bool,
 }
 
 impl Statusbar {
@@ -21,6 +27,8 @@ impl Statusbar {
                 it.set_color(c.clone());
             })
             .mrc();
+        let clip = Label::new("").also(|l| l.set_color(c)).mrc();
+        let task = Label::new("").also(|l| l.set_color(c)).mrc();
         Statusbar {
             main: Background::new(
                 SizedBox::new(
@@ -31,7 +39,10 @@ impl Statusbar {
                                 Label::new("status bar")
                                     .also(|l| l.set_color(c.clone()))
                                     .mrc(),
-                            )
+                            );
+                            it.add_flex(Space::new().mrc(), 1);
+                            it.add(task.clone());
+                            it.add(clip.clone());
                         })
                         .mrc(),
                 )
@@ -40,11 +51,57 @@ impl Statusbar {
                 Color::Cyan,
             ),
             spinner: sp,
+            clip,
+            task,
+            loading: false,
+            task_count: 0,
+            suspended: false,
         }
     }
 
+    pub fn set_clip_count(&mut self, count: usize) {
+        let txt = if count == 0 {
+            "".to_string()
+        } else {
+            format!("[clip {}] ", count)
+        };
+        self.clip.borrow_mut().set_text(txt);
+        self.redraw();
+    }
+
+    pub fn set_task_count(&mut self, count: usize) {
+        let txt = if count == 0 {
+            "".to_string()
+        } else {
+            format!("[task {}] ", count)
+        };
+        self.task_count = count;
+        self.task.borrow_mut().set_text(txt);
+        self.update_spin();
+        self.redraw();
+    }
+
     pub fn set_spin(&mut self, s: bool) {
-        if s {
+        self.loading = s;
+        self.update_spin();
+    }
+
+    // the spinner draws by itself, so it is stopped while the ui is suspended
+    pub fn set_suspended(&mut self, suspended: bool) {
+        self.suspended = suspended;
+        if suspended {
+            self.spinner.borrow_mut().stop();
+        } else {
+            self.update_spin();
+        }
+    }
+
+    // keep spinning while loading or any task is running
+    fn update_spin(&mut self) {
+        if self.suspended {
+            return;
+        }
+        if self.loading || self.task_count > 0 {
             self.spinner.borrow_mut().start();
         } else {
             self.spinner.borrow_mut().end();
@@ -1,5 +1,6 @@
 use crate::common::Functional;
 use crate::ui::base::draw::Draw;
+use crate::ui::base::shape::{Point, Size};
 use crate::ui::layout::container::Container;
 use crate::ui::layout::flex::Flex;
 use crate::ui::layout::padding::Padding;
@@ -12,6 +13,12 @@ use crate::ui::main::statusbar::Statusbar;
 use crate::ui::widget::label::Label;
 use crate::ui::widget::tab::Tab;
 use crate::ui::{InnerFunctional, Mrc, ToMrc};
+use crossterm::cursor::{Hide, Show};
+use crossterm::execute;
+use crossterm::terminal::{
+    disable_raw_mode, enable_raw_mode, size, Clear, ClearType, EnterAlternateScreen,
+    LeaveAlternateScreen,
+};
 use std::cell::RefMut;
 use std::io::{stdout, Write};
 
@@ -100,6 +107,10 @@ impl UI {
         self.path.borrow_mut()
     }
 
+    pub fn statusbar_mut(&mut self) -> RefMut<'_, Statusbar> {
+        self.statusbar.borrow_mut()
+    }
+
     pub fn show_key_nav(&mut self, navs: Vec<(String, String)>) {
         self.show_message = 2;
         self.message.inner_apply(|mut it| {
@@ -111,6 +122,15 @@ impl UI {
         });
     }
 
+    pub fn show_message(&mut self, msg: String) {
+        self.show_message = 2;
+        self.message.inner_apply(|mut it| {
+            it.empty_it();
+            it.add(Label::from(msg).mrc());
+            it.redraw();
+        });
+    }
+
     pub fn show_input(&mut self, prompt: String) {
         self.show_message = 2;
         self.input.inner_apply(|mut it| it.init(prompt));
@@ -147,6 +167,26 @@ impl UI {
         });
     }
 
+    // give the terminal back to the shell, nothing is drawn until resumed
+    pub fn suspend(&mut self) {
+        self.statusbar.borrow_mut().set_suspended(true);
+        execute!(stdout(), Show, LeaveAlternateScreen).unwrap();
+        disable_raw_mode().unwrap();
+    }
+
+    // take the terminal back and draw everything again, the size may be changed meanwhile
+    pub fn resume(&mut self) {
+        enable_raw_mode().unwrap();
+        execute!(stdout(), EnterAlternateScreen, Clear(ClearType::All), Hide).unwrap();
+        let (width, height) = size().unwrap();
+        let size = Size::new(width, height);
+        self.ensure(&size, &size);
+        self.move_to(&Point::new(0, 0));
+        self.draw();
+        self.statusbar.borrow_mut().set_suspended(false);
+        self.flush();
+    }
+
     pub fn flush(&mut self) {
         stdout().flush().unwrap();
     }
@@ -8,6 +8,7 @@ use crate::ui::ToMrc;
 use crossterm::style::Colors;
 use std::io::{stdout, Write};
 use std::sync::{Arc, Mutex, RwLock};
+use std::thread::JoinHandle;
 use std::{thread, time};
 
 const OK: &'static str = "✓";
@@ -17,6 +18,7 @@ pub struct Spinner {
     main: Quoted,
     started: Arc<RwLock<bool>>,
     chars: Vec<char>,
+    handle: Option<JoinHandle<()>>,
 }
 
 impl Spinner {
@@ -27,6 +29,7 @@ impl Spinner {
             label,
             started: Arc::new(RwLock::new(false)),
             chars: "-\\|/".chars().collect(),
+            handle: None,
         }
     }
 
@@ -49,7 +52,7 @@ impl Spinner {
         let len = self.chars.len();
         let chars = self.chars.clone();
         let label = self.label.clone();
-        thread::spawn(move || {
+        self.handle = Some(thread::spawn(move || {
             let mut i = 0usize;
             while *lock.read().unwrap() {
                 i += 1;
@@ -59,7 +62,7 @@ impl Spinner {
                 thread::sleep(time::Duration::from_millis(100));
             }
             Spinner::update_char(&label, OK.to_string());
-        });
+        }));
     }
 
     pub fn end(&mut self) {
@@ -67,6 +70,14 @@ impl Spinner {
         *s = false;
     }
 
+    // end and wait for the spinning thread, so nothing is drawn after it returns
+    pub fn stop(&mut self) {
+        self.end();
+        if let Some(h) = self.handle.take() {
+            h.join().unwrap();
+        }
+    }
+
     fn update_char(label: &Arc<Mutex<Label>>, c: String) {
         let mut it = label.lock().unwrap();
         it.set_text(c);